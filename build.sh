@@ -4,3 +4,8 @@ cargo build --release
 cargo fmt --all -- --check
 cargo clippy --all-targets --all-features --no-deps
 cargo test
+cargo test --features saturating-narrow
+cargo test --features unchecked-capacity
+cargo test --features result-checks
+# Library calls to defmt::error! only link against the tests' global logger.
+cargo test --features defmt-checks --lib
@@ -0,0 +1,331 @@
+use core::mem::size_of;
+use heapless::HistoryBuffer;
+
+use crate::{
+    Accumulator, Coefficients, MovingAverageError, Sample,
+    error::{check, convert, finish_check, unwrap_check},
+    integer::{abs_diff, fits_in_accumulator, fits_squares_in_accumulator},
+};
+
+/// # Intent
+/// Creates a Moving Average filter whose effective depth adapts to the
+/// signal, lengthening while it is stable for more smoothing and
+/// shortening while it changes for less lag.
+///
+/// # Choosing the effective length
+///
+/// Up to `MAX_N` samples are stored. After each insertion, the population
+/// variance of the newest `min_len` samples is compared with the
+/// variance threshold:
+///
+/// * At or below the threshold, the effective length grows by one sample,
+///   up to `MAX_N`.
+/// * Above it, the effective length drops straight back to `min_len`.
+///
+/// The average is then the mean of the newest effective-length samples.
+/// Unlike `MovingAverage` there is no running sum, so each update costs
+/// O(`MAX_N`). The variance requires `MAX_N * (T::MAX - T::MIN)²` to fit
+/// in TCALC, for any `min_len`, e.g. a `u8` in `u32` or a `u16` in `u64`.
+///
+/// As with `MovingAverage`, the first insertion stuffs the entire buffer
+/// with the first value.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::AdaptiveWindowAverage;
+///
+/// let mut sut = AdaptiveWindowAverage::<u8, u32, 8>::new(2, 4);
+/// for _ in 0..8 {
+///     let _ = sut.average(100);
+/// }
+/// assert_eq!(8, sut.effective_len());
+///
+/// let _ = sut.average(200);
+/// assert_eq!(2, sut.effective_len());
+/// ```
+///
+/// # Compile-time checks
+///
+/// ## `MAX_N` * `T::MAX` must fit in TCALC
+///
+/// Unless the `unchecked-capacity` feature is enabled.
+///
+#[cfg_attr(not(feature = "unchecked-capacity"), doc = "```compile_fail")]
+#[cfg_attr(feature = "unchecked-capacity", doc = "```ignore")]
+/// use no_std_moving_average::AdaptiveWindowAverage;
+/// let _sut = AdaptiveWindowAverage::<u8, u16, 512>::new(2, 4);
+/// ```
+///
+/// ## `MAX_N` * (`T::MAX` - `T::MIN`)² must fit in TCALC
+///
+/// ```compile_fail
+/// use no_std_moving_average::AdaptiveWindowAverage;
+/// let _sut = AdaptiveWindowAverage::<u16, u32, 8>::new(8, u32::MAX);
+/// ```
+///
+pub struct AdaptiveWindowAverage<T, TCALC, const MAX_N: usize>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    min_len: usize,
+    len: usize,
+    var_threshold: TCALC,
+    buffer: HistoryBuffer<T, MAX_N>,
+}
+
+impl<T, TCALC, const MAX_N: usize> AdaptiveWindowAverage<T, TCALC, MAX_N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics if TCALC not larger than T, compile-time assert.
+    /// Panics if `MAX_N` is zero, compile-time assert.
+    /// Panics if `MAX_N` * `T::MAX` won't fit in TCALC, without the
+    /// `unchecked-capacity` feature, compile-time assert.
+    /// Panics if `MAX_N` * (`T::MAX` - `T::MIN`)² won't fit in TCALC,
+    /// compile-time assert.
+    /// Panics if `min_len` is not in `1..=MAX_N`.
+    /// : These panics happen at allocation time, so should be found predictably.
+    /// With the `defmt-checks` feature, logs the error and clamps `min_len`
+    /// into `1..=MAX_N` instead.
+    #[must_use]
+    pub fn new(min_len: usize, var_threshold: TCALC) -> Self {
+        finish_check(Self::with_min_len(min_len, var_threshold), || {
+            Self::with_clamped_min_len(min_len, var_threshold)
+        })
+    }
+
+    /// # Intent
+    /// Creates a filter as `new` does, for callers that handle a bad
+    /// `min_len` themselves.
+    ///
+    /// # Errors
+    /// Returns `InvalidLength` if `min_len` is not in `1..=MAX_N`.
+    #[cfg(feature = "result-checks")]
+    pub fn try_new(min_len: usize, var_threshold: TCALC) -> Result<Self, MovingAverageError> {
+        Self::with_min_len(min_len, var_threshold)
+    }
+
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T.
+    /// This panic should never occur due to the allocation time asserts.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        if self.buffer.is_empty() {
+            for _ in 0..MAX_N {
+                self.buffer.write(input);
+            }
+        }
+        self.buffer.write(input);
+        self.len = if self.variance_of_newest(self.min_len) > self.var_threshold {
+            self.min_len
+        } else {
+            (self.len + 1).min(MAX_N)
+        };
+        unwrap_check(convert(
+            self.mean_of_newest(self.len),
+            MovingAverageError::OutputOverflow,
+        ))
+    }
+
+    /// # Intent
+    /// Returns the number of samples the current average is taken over.
+    #[must_use]
+    pub fn effective_len(&self) -> usize {
+        self.len
+    }
+
+    fn with_min_len(min_len: usize, var_threshold: TCALC) -> Result<Self, MovingAverageError> {
+        const {
+            assert!(
+                size_of::<TCALC>() > size_of::<T>(),
+                "TCALC must be larger than T"
+            );
+            assert!(MAX_N > 0, "MAX_N must be non-zero");
+            assert!(
+                cfg!(feature = "unchecked-capacity") || fits_in_accumulator::<T, TCALC>(MAX_N),
+                "N * T.max() must fit in TCALC"
+            );
+            assert!(
+                fits_squares_in_accumulator::<T, TCALC>(MAX_N),
+                "N * (T.max() - T.min())^2 must fit in TCALC"
+            );
+        }
+        Self::check_min_len(min_len)?;
+        Ok(Self::with_clamped_min_len(min_len, var_threshold))
+    }
+
+    fn with_clamped_min_len(min_len: usize, var_threshold: TCALC) -> Self {
+        let clamped = min_len.clamp(1, MAX_N);
+        Self {
+            min_len: clamped,
+            len: clamped,
+            var_threshold,
+            buffer: HistoryBuffer::new(),
+        }
+    }
+
+    fn check_min_len(min_len: usize) -> Result<(), MovingAverageError> {
+        check!(
+            min_len > 0 && min_len <= MAX_N,
+            MovingAverageError::InvalidLength
+        );
+        Ok(())
+    }
+
+    fn newest(&self, len: usize) -> impl Iterator<Item = &T> {
+        self.buffer.oldest_ordered().skip(MAX_N - len)
+    }
+
+    fn mean_of_newest(&self, len: usize) -> TCALC {
+        let count = unwrap_check(convert(len, MovingAverageError::CountOverflow));
+        self.newest(len)
+            .fold(TCALC::ZERO, |sum, &value| sum + TCALC::from(value))
+            / count
+    }
+
+    /// The squares fit in TCALC for any `len` up to `MAX_N`, by the
+    /// compile-time assert.
+    fn variance_of_newest(&self, len: usize) -> TCALC {
+        let count = unwrap_check(convert(len, MovingAverageError::CountOverflow));
+        let mean = self.mean_of_newest(len);
+        let squares = self.newest(len).fold(TCALC::ZERO, |squares, &value| {
+            let deviation = abs_diff(TCALC::from(value), mean);
+            squares + deviation * deviation
+        });
+        squares / count
+    }
+}
+
+/// # Intent
+/// The newest effective-length samples have a weight of one, and the
+/// rest of the window zero.
+impl<T, TCALC, const MAX_N: usize> Coefficients<TCALC, MAX_N>
+    for AdaptiveWindowAverage<T, TCALC, MAX_N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn coefficients(&self) -> [TCALC; MAX_N] {
+        let mut coefficients = [TCALC::ZERO; MAX_N];
+        for coefficient in &mut coefficients[MAX_N - self.len..] {
+            *coefficient = TCALC::ONE;
+        }
+        coefficients
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::AdaptiveWindowAverage;
+    use crate::{Coefficients as _, MovingAverageError};
+
+    #[test]
+    fn given_flat_input_when_average_then_effective_window_grows_to_max() {
+        let mut sut = AdaptiveWindowAverage::<u8, u32, 8>::new(2, 4_u32);
+        assert_eq!(100_u8, sut.average(100_u8));
+        assert_eq!(3, sut.effective_len());
+        for _ in 0..8_usize {
+            assert_eq!(100_u8, sut.average(100_u8));
+        }
+        assert_eq!(8, sut.effective_len());
+    }
+
+    #[test]
+    fn given_ramp_when_average_then_effective_window_stays_short() {
+        let mut sut = AdaptiveWindowAverage::<u8, u32, 8>::new(2, 4_u32);
+        for _ in 0..8_usize {
+            let _ = sut.average(100_u8);
+        }
+        for val in [110_u8, 120_u8, 130_u8, 140_u8] {
+            let _ = sut.average(val);
+            assert_eq!(2, sut.effective_len());
+        }
+        assert_eq!(145_u8, sut.average(150_u8));
+    }
+
+    #[test]
+    fn given_ramp_then_flat_when_average_then_effective_window_regrows() {
+        let mut sut = AdaptiveWindowAverage::<u8, u32, 8>::new(2, 4_u32);
+        for val in [100_u8, 110_u8, 120_u8] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(2, sut.effective_len());
+        assert_eq!(116_u8, sut.average(120_u8));
+        assert_eq!(3, sut.effective_len());
+        assert_eq!(117_u8, sut.average(120_u8));
+        assert_eq!(4, sut.effective_len());
+    }
+
+    #[test]
+    fn given_effective_len_when_coefficients_then_weight_only_newest_samples() {
+        let mut sut = AdaptiveWindowAverage::<u8, u32, 4>::new(2, 4_u32);
+        let _ = sut.average(100_u8);
+        assert_eq!([0_u32, 1_u32, 1_u32, 1_u32], sut.coefficients());
+    }
+
+    #[test]
+    fn given_invalid_min_len_when_check_min_len_then_return_invalid_length() {
+        assert_eq!(
+            Err(MovingAverageError::InvalidLength),
+            AdaptiveWindowAverage::<u8, u32, 8>::check_min_len(0)
+        );
+        assert_eq!(
+            Ok(()),
+            AdaptiveWindowAverage::<u8, u32, 8>::check_min_len(8)
+        );
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "window length must be in 1..=N")]
+    fn confirm_min_len_is_non_zero() {
+        let _sut = AdaptiveWindowAverage::<u8, u32, 8>::new(0, 4_u32);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "window length must be in 1..=N")]
+    fn confirm_min_len_is_not_above_max_n() {
+        let _sut = AdaptiveWindowAverage::<u8, u32, 8>::new(9, 4_u32);
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_invalid_min_len_when_try_new_then_return_invalid_length() {
+        assert_eq!(
+            Some(MovingAverageError::InvalidLength),
+            AdaptiveWindowAverage::<u8, u32, 8>::try_new(9, 4_u32).err()
+        );
+        assert!(AdaptiveWindowAverage::<u8, u32, 8>::try_new(8, 4_u32).is_ok());
+    }
+
+    #[cfg(feature = "defmt-checks")]
+    #[test]
+    fn given_defmt_checks_when_min_len_is_invalid_then_clamp_it() {
+        let too_short = AdaptiveWindowAverage::<u8, u32, 8>::new(0, 4_u32);
+        assert_eq!(1, too_short.effective_len());
+        let too_long = AdaptiveWindowAverage::<u8, u32, 8>::new(9, 4_u32);
+        assert_eq!(8, too_long.effective_len());
+    }
+
+    #[test]
+    fn given_full_range_alternation_when_average_then_variance_does_not_overflow() {
+        let mut sut = AdaptiveWindowAverage::<u16, u64, 8>::new(8, u64::MAX);
+        for val in [0_u16, 0xFFFF_u16].into_iter().cycle().take(16) {
+            let _ = sut.average(val);
+        }
+        assert_eq!(8, sut.effective_len());
+        assert_eq!(0x7FFF_u16, sut.average(0_u16));
+        // Deviations of 0x7FFF and 0x8000 from a truncated mean of 0x7FFF.
+        assert_eq!(
+            u64::midpoint(0x7FFF_u64 * 0x7FFF_u64, 0x8000_u64 * 0x8000_u64),
+            sut.variance_of_newest(8)
+        );
+    }
+}
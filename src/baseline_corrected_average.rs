@@ -0,0 +1,111 @@
+use crate::{Accumulator, MovingAverage, Sample};
+
+/// # Intent
+/// Removes slow drift from a signal, by reporting a short moving average
+/// minus a long moving average baseline, both fed the same samples.
+///
+/// The difference is calculated in TCALC. It is negative whenever the
+/// signal is below its baseline, so a signed TCALC, e.g. `u16` in `i32`,
+/// is needed to follow both sides. With an unsigned TCALC a negative
+/// difference is reported as zero.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::BaselineCorrectedAverage;
+///
+/// let mut sut = BaselineCorrectedAverage::<u16, i32, 2, 8>::new();
+/// let _ = sut.update(100);
+/// assert_eq!(0, sut.update(100));
+/// assert_eq!(23, sut.update(160));
+/// ```
+pub struct BaselineCorrectedAverage<T, TCALC, const FAST: usize, const SLOW: usize>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fast: MovingAverage<T, TCALC, FAST>,
+    slow: MovingAverage<T, TCALC, SLOW>,
+}
+
+impl<T, TCALC, const FAST: usize, const SLOW: usize> Default
+    for BaselineCorrectedAverage<T, TCALC, FAST, SLOW>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn default() -> Self {
+        Self {
+            fast: MovingAverage::new(),
+            slow: MovingAverage::new(),
+        }
+    }
+}
+
+impl<T, TCALC, const FAST: usize, const SLOW: usize> BaselineCorrectedAverage<T, TCALC, FAST, SLOW>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics as `MovingAverage::new`, for either of the FAST or SLOW depths.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Intent
+    /// Averages `input` into both filters, returning the fast average minus
+    /// the slow baseline.
+    #[must_use]
+    pub fn update(&mut self, input: T) -> TCALC {
+        let fast = TCALC::from(self.fast.average(input));
+        let slow = TCALC::from(self.slow.average(input));
+        if fast < slow && !TCALC::SIGNED {
+            TCALC::ZERO
+        } else {
+            fast - slow
+        }
+    }
+
+    /// # Intent
+    /// Returns the slow baseline, or `None` if no samples have been
+    /// recorded.
+    #[must_use]
+    pub fn baseline(&self) -> Option<T> {
+        self.slow.current_average()
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::BaselineCorrectedAverage;
+
+    #[test]
+    fn given_drift_with_fast_feature_when_update_then_baseline_is_removed() {
+        let mut sut = BaselineCorrectedAverage::<u16, i32, 2, 16>::new();
+        let mut drift = 1000_u16;
+        for _ in 0..32_usize {
+            drift += 1_u16;
+            assert!(sut.update(drift).abs() <= 8_i32);
+        }
+        let _ = sut.update(drift + 200_u16);
+        assert!(sut.update(drift + 200_u16) >= 180_i32);
+        for _ in 0..32_usize {
+            drift += 1_u16;
+            let _ = sut.update(drift);
+        }
+        assert!(sut.update(drift).abs() <= 8_i32);
+        assert!(sut.baseline().is_some_and(|baseline| baseline < drift));
+    }
+
+    #[test]
+    fn given_unsigned_tcalc_when_signal_drops_below_baseline_then_return_zero() {
+        let mut sut = BaselineCorrectedAverage::<u16, u32, 1, 4>::new();
+        let _ = sut.update(100_u16);
+        assert_eq!(0_u32, sut.update(20_u16));
+        assert_eq!(95_u32, sut.update(200_u16));
+    }
+}
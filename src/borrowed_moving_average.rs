@@ -0,0 +1,222 @@
+use core::mem::size_of;
+
+use crate::{
+    Accumulator, MovingAverageError, Sample,
+    error::{check, convert, finish_check, unwrap_check},
+    integer::fits_in_accumulator,
+};
+
+/// # Intent
+/// A Moving Average filter over storage supplied by the caller, rather than
+/// an owned `HistoryBuffer`, so the window can be placed in a specific
+/// memory region. The depth of the average is the length of the slice.
+///
+/// The behavior matches `MovingAverage`, including stuffing the entire
+/// window with the first value.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::BorrowedMovingAverage;
+///
+/// let mut storage = [0_u16; 2];
+/// let mut sut = BorrowedMovingAverage::<u16, u32>::new(&mut storage);
+/// let _ = sut.average(22);
+/// let _ = sut.average(44);
+///
+/// assert_eq!(55, sut.average(66));
+/// ```
+pub struct BorrowedMovingAverage<'a, T, TCALC>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    num: TCALC,
+    sum: Option<TCALC>,
+    window: &'a mut [T],
+    oldest: usize,
+}
+
+impl<'a, T, TCALC> BorrowedMovingAverage<'a, T, TCALC>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics if TCALC not larger than T, compile-time assert.
+    /// Panics if `storage` is empty.
+    /// Panics if `storage.len()` * `T::MAX` won't fit in TCALC.
+    /// : These panics happen at allocation time, so should be found predictably.
+    /// With the `defmt-checks` feature, logs the error and uses only the
+    /// longest prefix of `storage` that fits instead. Empty storage still
+    /// panics, as there is no window to fall back to.
+    #[must_use]
+    pub fn new(storage: &'a mut [T]) -> Self {
+        let len = finish_check(Self::check_storage(storage.len()), || {
+            Self::fitting_len(storage.len())
+        });
+        unwrap_check(Self::with_storage(storage.split_at_mut(len).0))
+    }
+
+    /// # Intent
+    /// Creates a filter over `storage` as `new` does, for callers that
+    /// handle unusable storage themselves.
+    ///
+    /// # Errors
+    /// Returns `InvalidLength` if `storage` is empty, or `CapacityOverflow`
+    /// if `storage.len()` * `T::MAX` won't fit in TCALC.
+    #[cfg(feature = "result-checks")]
+    pub fn try_new(storage: &'a mut [T]) -> Result<Self, MovingAverageError> {
+        Self::with_storage(storage)
+    }
+
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T.
+    /// This panic should never occur due to the allocation time asserts.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        let new_value = TCALC::from(input);
+        let prev_sum = self.sum.unwrap_or_else(|| {
+            self.window.fill(input);
+            self.num * new_value
+        });
+        let remove = TCALC::from(self.window[self.oldest]);
+        self.window[self.oldest] = input;
+        self.oldest = (self.oldest + 1) % self.window.len();
+        // Removing first keeps the intermediate within the sum of the window.
+        let new_sum = prev_sum - remove + new_value;
+        self.sum = Some(new_sum);
+        unwrap_check(convert(
+            new_sum / self.num,
+            MovingAverageError::OutputOverflow,
+        ))
+    }
+
+    /// # Intent
+    /// Returns the average of the current window without inserting a
+    /// sample, or `None` if no samples have been recorded.
+    #[must_use]
+    pub fn current_average(&self) -> Option<T> {
+        T::try_from(self.sum? / self.num).ok()
+    }
+
+    fn with_storage(storage: &'a mut [T]) -> Result<Self, MovingAverageError> {
+        const {
+            assert!(
+                size_of::<TCALC>() > size_of::<T>(),
+                "TCALC must be larger than T"
+            );
+        }
+        Self::check_storage(storage.len())?;
+        Ok(Self {
+            num: convert(storage.len(), MovingAverageError::CapacityOverflow)?,
+            sum: None,
+            window: storage,
+            oldest: 0,
+        })
+    }
+
+    fn check_storage(len: usize) -> Result<usize, MovingAverageError> {
+        Self::check_len(len)?;
+        Self::check_capacity(len)?;
+        Ok(len)
+    }
+
+    /// The longest window, up to `len`, whose sum fits in TCALC.
+    fn fitting_len(len: usize) -> usize {
+        (1..=len)
+            .take_while(|&window| fits_in_accumulator::<T, TCALC>(window))
+            .count()
+    }
+
+    fn check_len(len: usize) -> Result<(), MovingAverageError> {
+        check!(len > 0, MovingAverageError::InvalidLength);
+        Ok(())
+    }
+
+    fn check_capacity(len: usize) -> Result<(), MovingAverageError> {
+        check!(
+            fits_in_accumulator::<T, TCALC>(len),
+            MovingAverageError::CapacityOverflow
+        );
+        Ok(())
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::BorrowedMovingAverage;
+    use crate::MovingAverage;
+    #[cfg(feature = "result-checks")]
+    use crate::MovingAverageError;
+
+    #[test]
+    fn given_stack_slice_when_average_then_match_moving_average() {
+        let mut storage = [0_i16; 4];
+        let mut sut = BorrowedMovingAverage::<i16, i32>::new(&mut storage);
+        let mut reference = MovingAverage::<i16, i32, 4>::new();
+        assert_eq!(None, sut.current_average());
+        for (i, val) in [5_i16, -20_i16, 300_i16, 7_i16, -1_i16, 42_i16, 9_i16]
+            .iter()
+            .enumerate()
+        {
+            assert_eq!(reference.average(*val), sut.average(*val), "Failed at {i}");
+        }
+        assert_eq!(reference.current_average(), sut.current_average());
+    }
+
+    #[test]
+    fn given_averaged_values_when_storage_released_then_it_holds_the_window() {
+        let mut storage = [0_u16; 3];
+        let mut sut = BorrowedMovingAverage::<u16, u32>::new(&mut storage);
+        for val in [1_u16, 2_u16, 3_u16, 4_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!([4_u16, 2_u16, 3_u16], storage);
+    }
+
+    #[test]
+    #[should_panic(expected = "window length must be in 1..=N")]
+    fn confirm_storage_is_non_empty() {
+        let mut storage: [u16; 0] = [];
+        let _sut = BorrowedMovingAverage::<u16, u32>::new(&mut storage);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "N * T.max() must fit in TCALC")]
+    fn confirm_storage_len_times_t_max_fits_in_tcalc() {
+        let mut storage = [0_u8; 512];
+        let _sut = BorrowedMovingAverage::<u8, u16>::new(&mut storage);
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_storage_len_times_t_max_overflows_when_try_new_then_return_capacity_overflow() {
+        let mut storage = [0_u8; 512];
+        assert_eq!(
+            Some(MovingAverageError::CapacityOverflow),
+            BorrowedMovingAverage::<u8, u16>::try_new(&mut storage).err()
+        );
+        let mut empty: [u8; 0] = [];
+        assert_eq!(
+            Some(MovingAverageError::InvalidLength),
+            BorrowedMovingAverage::<u8, u16>::try_new(&mut empty).err()
+        );
+        let mut fitting = [0_u8; 256];
+        assert!(BorrowedMovingAverage::<u8, u16>::try_new(&mut fitting).is_ok());
+    }
+
+    #[cfg(feature = "defmt-checks")]
+    #[test]
+    fn given_defmt_checks_when_storage_is_too_long_then_use_the_prefix_that_fits() {
+        let mut storage = [0_u8; 512];
+        let mut sut = BorrowedMovingAverage::<u8, u16>::new(&mut storage);
+        assert_eq!(255_u8, sut.average(255_u8));
+        assert_eq!(254_u8, sut.average(0_u8));
+        assert_eq!([255_u8, 0_u8], storage[..2]);
+        assert_eq!([255_u8, 0_u8], storage[255..257]);
+    }
+}
@@ -0,0 +1,117 @@
+use core::mem::size_of;
+
+use crate::{
+    Accumulator, MovingAverageError, Sample,
+    error::{convert, unwrap_check},
+    integer::fits_in_accumulator,
+};
+
+/// # Intent
+/// Writes the zero-phase moving average of `input` into `out`, for batch
+/// processing of a complete dataset. Each output is the average of the N
+/// samples centered on its position, so unlike the causal `MovingAverage`
+/// the output doesn't lag the input. For an even N the window holds one
+/// more sample before the position than after it.
+///
+/// Near the ends of `input` the window is cut short by the edge, and the
+/// average is taken over the samples that remain. If `out` is shorter than
+/// `input`, only the first `out.len()` averages are written, still using
+/// the later inputs in their windows.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::centered_average_into;
+///
+/// let mut out = [0_u16; 5];
+/// centered_average_into::<u16, u32, 3>(&[0, 10, 20, 30, 40], &mut out);
+///
+/// assert_eq!([5, 10, 20, 30, 35], out);
+/// ```
+///
+/// # Compile-time checks
+///
+/// ## N * `T::MAX` must fit in TCALC
+///
+/// Unless the `unchecked-capacity` feature is enabled.
+///
+#[cfg_attr(not(feature = "unchecked-capacity"), doc = "```compile_fail")]
+#[cfg_attr(feature = "unchecked-capacity", doc = "```ignore")]
+/// use no_std_moving_average::centered_average_into;
+/// let mut out = [0_u8; 2];
+/// centered_average_into::<u8, u16, 512>(&[0_u8; 2], &mut out);
+/// ```
+///
+/// # Panics
+/// Panics if TCALC not larger than T, compile-time assert.
+/// Panics if N is zero, compile-time assert.
+/// Panics if N * `T::MAX` won't fit in TCALC, without the
+/// `unchecked-capacity` feature, compile-time assert.
+pub fn centered_average_into<T, TCALC, const N: usize>(input: &[T], out: &mut [T])
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    const {
+        assert!(
+            size_of::<TCALC>() > size_of::<T>(),
+            "TCALC must be larger than T"
+        );
+        assert!(N > 0, "N must be non-zero");
+        assert!(
+            cfg!(feature = "unchecked-capacity") || fits_in_accumulator::<T, TCALC>(N),
+            "N * T.max() must fit in TCALC"
+        );
+    }
+    let before = N / 2;
+    let after = (N - 1) / 2;
+    let mut sum = TCALC::ZERO;
+    let (mut start, mut end) = (0, 0);
+    for (i, slot) in out.iter_mut().take(input.len()).enumerate() {
+        // Removing first keeps the intermediate within the sum of N samples.
+        while start < i.saturating_sub(before) {
+            sum = sum - TCALC::from(input[start]);
+            start += 1;
+        }
+        while end < (i + after + 1).min(input.len()) {
+            sum = sum + TCALC::from(input[end]);
+            end += 1;
+        }
+        let count = unwrap_check(convert(end - start, MovingAverageError::CountOverflow));
+        *slot = unwrap_check(convert(sum / count, MovingAverageError::OutputOverflow));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::centered_average_into;
+    use crate::MovingAverage;
+
+    const RAMP: [u16; 10] = [
+        0_u16, 10_u16, 20_u16, 30_u16, 40_u16, 50_u16, 60_u16, 70_u16, 80_u16, 90_u16,
+    ];
+
+    #[test]
+    fn given_ramp_when_centered_average_into_then_interior_has_no_lag() {
+        let mut out = [0_u16; 10];
+        centered_average_into::<u16, u32, 3>(&RAMP, &mut out);
+        let mut causal = MovingAverage::<u16, u32, 3, false>::new();
+        for (i, val) in RAMP.iter().enumerate() {
+            let lagging = causal.average(*val);
+            if (1..9).contains(&i) {
+                assert_eq!(*val, out[i], "Failed at {i}");
+            }
+            if i >= 2 {
+                assert_eq!(*val - 10_u16, lagging, "Failed at {i}");
+            }
+        }
+        assert_eq!((5_u16, 85_u16), (out[0], out[9]));
+    }
+
+    #[test]
+    fn given_even_depth_when_centered_average_into_then_window_leans_back_one() {
+        let mut out = [0_u16; 4];
+        centered_average_into::<u16, u32, 4>(&RAMP[..6], &mut out);
+        assert_eq!([5_u16, 10_u16, 15_u16, 25_u16], out);
+    }
+}
@@ -0,0 +1,11 @@
+/// # Intent
+/// Reports the effective impulse response of a filter, to verify it against
+/// a spec or compute its frequency response offline.
+///
+/// The coefficients are scaled integers, oldest sample position first,
+/// and sum to the divisor of the average. For the simple moving average
+/// they are all one, summing to N.
+pub trait Coefficients<TCALC, const N: usize> {
+    /// Returns the weight of each window position, oldest first.
+    fn coefficients(&self) -> [TCALC; N];
+}
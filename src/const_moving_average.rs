@@ -0,0 +1,186 @@
+/// # Intent
+/// A `const` capable counterpart to `MovingAverage`, so sequences can be
+/// filtered while evaluating constants, e.g. to generate lookup tables at
+/// compile time.
+///
+/// `MovingAverage` cannot run in a `const` context, because neither
+/// `HistoryBuffer::write` nor the `From`/`TryFrom` conversions are `const`.
+/// This type keeps its window in a plain array and is implemented for each
+/// pair of unsigned integer types, where TCALC is larger than T, through
+/// per-type `const` conversions.
+///
+/// The behavior matches `MovingAverage`, including stuffing the entire
+/// window with the first value.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::ConstMovingAverage;
+///
+/// const AVERAGES: [u16; 3] = {
+///     let sut = ConstMovingAverage::<u16, u32, 2>::new();
+///     let (sut, first) = sut.average_const(22);
+///     let (sut, second) = sut.average_const(44);
+///     let (_, third) = sut.average_const(66);
+///     [first, second, third]
+/// };
+///
+/// assert_eq!([22, 33, 55], AVERAGES);
+/// ```
+///
+/// ## N must be non-zero
+///
+/// ```compile_fail
+/// use no_std_moving_average::ConstMovingAverage;
+/// let _sut = ConstMovingAverage::<u16, u32, 0>::new();
+/// ```
+///
+/// ## N * `T::MAX` must fit in TCALC
+///
+/// ```compile_fail
+/// use no_std_moving_average::ConstMovingAverage;
+/// let _sut = ConstMovingAverage::<u8, u16, 512>::new();
+/// ```
+#[derive(Clone, Copy)]
+pub struct ConstMovingAverage<T, TCALC, const N: usize> {
+    sum: Option<TCALC>,
+    window: [T; N],
+    oldest: usize,
+}
+
+macro_rules! impl_const_moving_average {
+    ($t:ty => $($tcalc:ty),+) => {
+        $(
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "Made safe by the compile-time capacity assert"
+            )]
+            impl<const N: usize> ConstMovingAverage<$t, $tcalc, N> {
+                #[must_use]
+                pub const fn new() -> Self {
+                    const {
+                        assert!(N > 0, "N must be non-zero");
+                        assert!(
+                            (N as u128) <= (<$tcalc>::MAX as u128) / (<$t>::MAX as u128),
+                            "N * T.max() must fit in TCALC"
+                        );
+                    }
+                    Self {
+                        sum: None,
+                        window: [0; N],
+                        oldest: 0,
+                    }
+                }
+
+                /// # Intent
+                /// Creates a filter whose window is `values`, oldest first,
+                /// fully primed so no warm-up fill happens.
+                #[must_use]
+                pub const fn new_from_array(values: [$t; N]) -> Self {
+                    let sut = Self::new();
+                    let mut sum: $tcalc = 0;
+                    let mut i = 0;
+                    while i < N {
+                        sum += values[i] as $tcalc;
+                        i += 1;
+                    }
+                    Self {
+                        sum: Some(sum),
+                        window: values,
+                        ..sut
+                    }
+                }
+
+                /// # Intent
+                /// Averages `input` into the window, returning the new state
+                /// alongside the new average.
+                #[must_use]
+                pub const fn average_const(mut self, input: $t) -> (Self, $t) {
+                    let new_value = input as $tcalc;
+                    let prev_sum = match self.sum {
+                        Some(sum) => sum,
+                        None => {
+                            self.window = [input; N];
+                            new_value * (N as $tcalc)
+                        }
+                    };
+                    let remove = self.window[self.oldest] as $tcalc;
+                    self.window[self.oldest] = input;
+                    self.oldest = (self.oldest + 1) % N;
+                    let new_sum = prev_sum - remove + new_value;
+                    self.sum = Some(new_sum);
+                    (self, (new_sum / (N as $tcalc)) as $t)
+                }
+            }
+
+            impl<const N: usize> Default for ConstMovingAverage<$t, $tcalc, N> {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+        )+
+    };
+}
+
+impl_const_moving_average!(u8 => u16, u32, u64, u128);
+impl_const_moving_average!(u16 => u32, u64, u128);
+impl_const_moving_average!(u32 => u64, u128);
+impl_const_moving_average!(u64 => u128);
+
+#[cfg(test)]
+mod tests {
+    use super::ConstMovingAverage;
+    use crate::MovingAverage;
+
+    const SEQUENCE: [u16; 12] = [
+        100_u16, 200_u16, 300_u16, 400_u16, 100_u16, 200_u16, 300_u16, 400_u16, 100_u16, 200_u16,
+        300_u16, 400_u16,
+    ];
+
+    const AVERAGES: [u16; 12] = {
+        let mut sut = ConstMovingAverage::<u16, u32, 4>::new();
+        let mut averages = [0_u16; 12];
+        let mut i = 0;
+        while i < SEQUENCE.len() {
+            let (next, average) = sut.average_const(SEQUENCE[i]);
+            sut = next;
+            averages[i] = average;
+            i += 1;
+        }
+        averages
+    };
+
+    #[test]
+    fn given_const_evaluated_sequence_when_compared_to_moving_average_then_match() {
+        let mut reference = MovingAverage::<u16, u32, 4>::new();
+        for (i, val) in SEQUENCE.iter().enumerate() {
+            let avg = reference.average(*val);
+            assert_eq!(
+                avg, AVERAGES[i],
+                "Failed at {i}, is {}, should be {avg}",
+                AVERAGES[i]
+            );
+        }
+    }
+
+    const PRIMED: ConstMovingAverage<u16, u32, 4> =
+        ConstMovingAverage::<u16, u32, 4>::new_from_array([10_u16, 20_u16, 30_u16, 40_u16]);
+
+    #[test]
+    fn given_const_array_when_new_from_array_then_window_is_primed() {
+        assert_eq!([10_u16, 20_u16, 30_u16, 40_u16], PRIMED.window);
+        assert_eq!(Some(100_u32), PRIMED.sum);
+        let (next, average) = PRIMED.average_const(50_u16);
+        assert_eq!(35_u16, average);
+        assert_eq!([50_u16, 20_u16, 30_u16, 40_u16], next.window);
+    }
+
+    #[test]
+    fn given_max_depth_when_average_max_then_return_max() {
+        let sut = ConstMovingAverage::<u8, u16, 257>::new();
+        let (next, first) = sut.average_const(u8::MAX);
+        let (_, second) = next.average_const(u8::MAX);
+        assert_eq!(u8::MAX, first);
+        assert_eq!(u8::MAX, second);
+    }
+}
@@ -0,0 +1,149 @@
+use core::{cmp::PartialOrd, fmt::Debug};
+
+#[cfg(feature = "result-checks")]
+use crate::MovingAverageError;
+use crate::{Accumulator, Ewma, MovingAverage, Sample, error::finish_check};
+
+/// # Intent
+/// Which way the fast line crossed the slow line, as returned by
+/// `Crossover::update`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CrossDirection {
+    /// The fast line moved above the slow line, as a rising trend starts.
+    Up,
+    /// The fast line moved below the slow line, as a falling trend starts.
+    Down,
+}
+
+/// # Intent
+/// A trend detector reporting when a fast `Ewma` crosses a slow N sample
+/// `MovingAverage`, both fed the same samples.
+///
+/// A crossing is a change of side. While the lines are equal the previous
+/// side is kept, so touching without passing is not a crossing, and no
+/// crossing is reported until the lines have first separated.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::{CrossDirection, Crossover};
+///
+/// let mut sut = Crossover::<u16, u32, 4>::new(1, 2);
+/// let _ = sut.update(100);
+/// assert_eq!(None, sut.update(120));
+/// assert_eq!(Some(CrossDirection::Down), sut.update(60));
+/// ```
+pub struct Crossover<T, TCALC, const N: usize>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fast: Ewma<T, TCALC>,
+    slow: MovingAverage<T, TCALC, N>,
+    fast_above: Option<bool>,
+}
+
+impl<T, TCALC, const N: usize> Crossover<T, TCALC, N>
+where
+    T: Sample<TCALC> + PartialOrd,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics as `Ewma::new` for the fast smoothing factor,
+    /// `alpha_num / alpha_den`, and as `MovingAverage::new` for N.
+    /// With the `defmt-checks` feature, logs the error and uses a fast line
+    /// that passes its input through instead.
+    #[must_use]
+    pub fn new(alpha_num: TCALC, alpha_den: TCALC) -> Self {
+        Self::with_fast(finish_check(
+            Ewma::with_alpha(alpha_num, alpha_den),
+            Ewma::pass_through,
+        ))
+    }
+
+    /// # Intent
+    /// Creates a detector as `new` does, for callers that handle a bad
+    /// smoothing factor themselves.
+    ///
+    /// # Errors
+    /// Returns the errors of `Ewma::try_new`.
+    #[cfg(feature = "result-checks")]
+    pub fn try_new(alpha_num: TCALC, alpha_den: TCALC) -> Result<Self, MovingAverageError> {
+        Ewma::with_alpha(alpha_num, alpha_den).map(Self::with_fast)
+    }
+
+    /// # Intent
+    /// Feeds `input` to both lines, returning the crossing if the fast line
+    /// changed side of the slow line.
+    #[must_use]
+    pub fn update(&mut self, input: T) -> Option<CrossDirection> {
+        let fast = self.fast.average(input);
+        let slow = self.slow.average(input);
+        if fast == slow {
+            return None;
+        }
+        let above = fast > slow;
+        let previous = self.fast_above.replace(above);
+        match (previous, above) {
+            (Some(false), true) => Some(CrossDirection::Up),
+            (Some(true), false) => Some(CrossDirection::Down),
+            _ => None,
+        }
+    }
+
+    fn with_fast(fast: Ewma<T, TCALC>) -> Self {
+        Self {
+            fast,
+            slow: MovingAverage::new(),
+            fast_above: None,
+        }
+    }
+}
+
+#[expect(clippy::expect_used, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::{CrossDirection, Crossover};
+    #[cfg(feature = "result-checks")]
+    use crate::MovingAverageError;
+    use heapless::Vec;
+
+    #[test]
+    fn given_trend_then_reversal_when_update_then_report_each_crossing() {
+        let mut sut = Crossover::<u16, u32, 8>::new(1_u32, 2_u32);
+        let mut events = Vec::<(usize, CrossDirection), 4>::new();
+        let rise = (0..10_u16).map(|step| 100_u16 + step * 20_u16);
+        let fall = (0..10_u16).map(|step| 280_u16 - step * 20_u16);
+        let rise_again = (0..10_u16).map(|step| 100_u16 + step * 20_u16);
+        for (i, val) in rise.chain(fall).chain(rise_again).enumerate() {
+            if let Some(direction) = sut.update(val) {
+                events.push((i, direction)).expect("Too many crossings");
+            }
+        }
+        assert_eq!(
+            [(13, CrossDirection::Down), (23, CrossDirection::Up)],
+            *events
+        );
+    }
+
+    #[test]
+    fn given_flat_signal_when_update_then_report_nothing() {
+        let mut sut = Crossover::<u16, u32, 4>::new(1_u32, 4_u32);
+        for _ in 0..16_usize {
+            assert_eq!(None, sut.update(500_u16));
+        }
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_bad_factor_when_try_new_then_return_error() {
+        assert_eq!(
+            Some(MovingAverageError::InvalidAlpha),
+            Crossover::<u16, u32, 4>::try_new(0_u32, 4_u32).err()
+        );
+        let mut sut = Crossover::<u16, u32, 4>::try_new(1_u32, 2_u32).expect("Valid factor");
+        assert_eq!(None, sut.update(500_u16));
+    }
+}
@@ -0,0 +1,136 @@
+use core::fmt::{self, Display, Formatter};
+
+/// # Intent
+/// The checks that can fail when configuring a filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MovingAverageError {
+    /// N * `T::MAX` won't fit in TCALC.
+    CapacityOverflow,
+    /// N squared differences of T won't fit in TCALC.
+    SquaresOverflow,
+    /// An output scale numerator or denominator is zero.
+    ZeroScale,
+    /// An output scale numerator or denominator is negative.
+    NegativeScale,
+    /// An output scale product won't fit in TCALC.
+    ScaleOverflow,
+    /// A window length is outside of the supported range.
+    InvalidLength,
+    /// A seed doesn't fill the window, which a prefilling filter requires.
+    IncompleteWindow,
+    /// A value to be removed isn't in the window.
+    NotInWindow,
+    /// The window holds no samples yet.
+    EmptyWindow,
+    /// A smoothing factor is zero or above one.
+    InvalidAlpha,
+    /// The full range of T times a smoothing factor numerator, or the
+    /// factor itself, won't fit in TCALC.
+    AlphaOverflow,
+    /// A weight is negative, or the weights sum to zero.
+    InvalidWeights,
+    /// The sum of the weights times `T::MAX`, or `T::MIN`, won't fit in
+    /// TCALC.
+    WeightedOverflow,
+    /// A count, such as a window length, ramp steps, or elapsed ticks,
+    /// won't fit in TCALC.
+    CountOverflow,
+    /// An average won't fit in T.
+    OutputOverflow,
+    /// A restored filter's depth, sum or sample count doesn't match its
+    /// window.
+    InconsistentState,
+}
+
+impl Display for MovingAverageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Self::CapacityOverflow => "N * T.max() must fit in TCALC",
+            Self::SquaresOverflow => "N * (T.max() - T.min())^2 must fit in TCALC",
+            Self::ZeroScale => "scale_num and scale_den must be non-zero",
+            Self::NegativeScale => "scale_num and scale_den must be non-negative",
+            Self::ScaleOverflow => "N * T.max() * scale_num and N * scale_den must fit in TCALC",
+            Self::InvalidLength => "window length must be in 1..=N",
+            Self::IncompleteWindow => "seed must hold N samples unless PREFILL is false",
+            Self::NotInWindow => "value is not in the window",
+            Self::EmptyWindow => "window holds no samples",
+            Self::InvalidAlpha => "alpha_num / alpha_den must be in (0, 1]",
+            Self::AlphaOverflow => "(T.max() - T.min()) * alpha_num must fit in TCALC",
+            Self::InvalidWeights => "weights must be non-negative, with a non-zero sum",
+            Self::WeightedOverflow => "sum(weights) * T.max() must fit in TCALC",
+            Self::CountOverflow => "count must fit in TCALC",
+            Self::OutputOverflow => "average must fit in T",
+            Self::InconsistentState => "num, sum and sample count must match the window",
+        })
+    }
+}
+
+/// Returns `Err($error)` from the enclosing function when `$condition` is
+/// false. All fallible checks in the crate go through this macro, so that
+/// the failure handling is decided in one place: the `try_*` methods of the
+/// `result-checks` feature return the error, `finish_check` handles it for
+/// the methods a caller can't check, and `unwrap_check` for the internal
+/// ones.
+macro_rules! check {
+    ($condition:expr, $error:expr) => {
+        if !$condition {
+            return Err($error);
+        }
+    };
+}
+pub(crate) use check;
+
+/// Converts `value` to W, failing with `error` if it won't fit.
+pub(crate) fn convert<V, W: TryFrom<V>>(
+    value: V,
+    error: MovingAverageError,
+) -> Result<W, MovingAverageError> {
+    W::try_from(value).ok().ok_or(error)
+}
+
+/// Hands the outcome of a check to a caller of a constructor or setter
+/// that doesn't return a `Result`. By default a failed check panics. With
+/// the `defmt-checks` feature the error is logged with `defmt::error!` and
+/// `fallback` supplies the value to continue with.
+///
+/// # Panics
+/// Panics with the error message if the check failed, without the
+/// `defmt-checks` feature.
+#[cfg(not(feature = "defmt-checks"))]
+pub(crate) fn finish_check<V>(
+    result: Result<V, MovingAverageError>,
+    _fallback: impl FnOnce() -> V,
+) -> V {
+    unwrap_check(result)
+}
+
+/// Hands the outcome of a check to a caller of a constructor or setter
+/// that doesn't return a `Result`. By default a failed check panics. With
+/// the `defmt-checks` feature the error is logged with `defmt::error!` and
+/// `fallback` supplies the value to continue with.
+#[cfg(feature = "defmt-checks")]
+pub(crate) fn finish_check<V>(
+    result: Result<V, MovingAverageError>,
+    fallback: impl FnOnce() -> V,
+) -> V {
+    result.unwrap_or_else(|error| {
+        defmt::error!("{}", error);
+        fallback()
+    })
+}
+
+/// Handles the outcome of a check for callers that can't return an error,
+/// such as trait impls and the per-sample paths whose checks are made safe
+/// at allocation time.
+///
+/// # Panics
+/// Panics with the error message if the check failed.
+#[expect(clippy::panic, reason = "The single panic path for failed checks")]
+pub(crate) fn unwrap_check<V>(result: Result<V, MovingAverageError>) -> V {
+    match result {
+        Ok(value) => value,
+        Err(error) => panic!("{error}"),
+    }
+}
@@ -0,0 +1,298 @@
+use core::{marker::PhantomData, mem::size_of};
+
+use crate::{
+    Accumulator, Filter, MovingAverageError, Sample,
+    error::{check, convert, finish_check, unwrap_check},
+    integer::{abs_diff, step_toward},
+};
+
+/// # Intent
+/// An exponentially weighted moving average, with O(1) state and no
+/// window. Each sample moves the average by `alpha_num / alpha_den` of
+/// its distance from the sample, `state += (input - state) * alpha`, in
+/// TCALC. The average starts at the first sample.
+///
+/// The step is truncated towards zero, so the average can settle short of
+/// a constant input by up to `alpha_den / alpha_num`.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::Ewma;
+///
+/// let mut sut = Ewma::<u16, u32>::new(1, 4);
+/// let _ = sut.average(100);
+///
+/// assert_eq!(125, sut.average(200));
+/// ```
+pub struct Ewma<T, TCALC>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    alpha_num: TCALC,
+    alpha_den: TCALC,
+    state: Option<TCALC>,
+    sample: PhantomData<T>,
+}
+
+impl<T, TCALC> Ewma<T, TCALC>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics if TCALC not larger than T, compile-time assert.
+    /// Panics if `alpha_num / alpha_den` is zero or above one.
+    /// Panics if `(T::MAX - T::MIN) * alpha_num` won't fit in TCALC.
+    /// : These panics happen at allocation time, so should be found predictably.
+    /// With the `defmt-checks` feature, logs the error and returns a filter
+    /// with a factor of one, which passes its input through, instead.
+    #[must_use]
+    pub fn new(alpha_num: TCALC, alpha_den: TCALC) -> Self {
+        finish_check(Self::with_alpha(alpha_num, alpha_den), Self::pass_through)
+    }
+
+    /// # Intent
+    /// Creates a filter as `new` does, for callers that handle a bad
+    /// smoothing factor themselves.
+    ///
+    /// # Errors
+    /// Returns `InvalidAlpha` if `alpha_num / alpha_den` is zero or above
+    /// one, or `AlphaOverflow` if `(T::MAX - T::MIN) * alpha_num` won't fit
+    /// in TCALC.
+    #[cfg(feature = "result-checks")]
+    pub fn try_new(alpha_num: TCALC, alpha_den: TCALC) -> Result<Self, MovingAverageError> {
+        Self::with_alpha(alpha_num, alpha_den)
+    }
+
+    /// # Intent
+    /// Creates a filter from a time constant `tau` in samples, after which
+    /// a step response has covered about 63% of the step, instead of from
+    /// a smoothing factor.
+    ///
+    /// The exact factor, `1 - e^(-1 / tau)`, is approximated without floats
+    /// by `2 / (2 * tau + 1)`. It is slightly high, by a relative error of
+    /// about `1 / (12 * tau^2)`: 1.7% at a `tau` of 2, 0.3% at 5, and under
+    /// 0.1% from 10 on. A `tau` of 1 gives 2/3 instead of 0.63.
+    ///
+    /// # Panics
+    /// Panics if `tau` is zero.
+    /// Panics if `2 * tau + 1` won't fit in TCALC.
+    /// Panics as `new`, for the resulting factor.
+    /// With the `defmt-checks` feature, logs the error and returns a filter
+    /// that passes its input through instead, as `new` does.
+    #[must_use]
+    pub fn from_time_constant(tau: u32) -> Self {
+        finish_check(Self::with_time_constant(tau), Self::pass_through)
+    }
+
+    /// # Intent
+    /// Creates a filter from a time constant as `from_time_constant` does,
+    /// for callers that handle a bad `tau` themselves.
+    ///
+    /// # Errors
+    /// Returns `InvalidAlpha` for a zero `tau`, `CountOverflow` if
+    /// `2 * tau + 1` won't fit in TCALC, and the errors of `try_new` for
+    /// the resulting factor.
+    #[cfg(feature = "result-checks")]
+    pub fn try_from_time_constant(tau: u32) -> Result<Self, MovingAverageError> {
+        Self::with_time_constant(tau)
+    }
+
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T.
+    /// This panic should never occur, as the average stays between the
+    /// smallest and largest samples.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        let value = TCALC::from(input);
+        let state = self.state.map_or(value, |state| {
+            step_toward(state, value, self.alpha_num, self.alpha_den)
+        });
+        self.state = Some(state);
+        unwrap_check(convert(state, MovingAverageError::OutputOverflow))
+    }
+
+    /// # Intent
+    /// Returns the current average without inserting a sample, or `None`
+    /// if no samples have been recorded.
+    #[must_use]
+    pub fn current_average(&self) -> Option<T> {
+        T::try_from(self.state?).ok()
+    }
+
+    /// Creates the filter for `new`, and for the constructors of the filters
+    /// built on it that can't return an error.
+    pub(crate) fn with_alpha(
+        alpha_num: TCALC,
+        alpha_den: TCALC,
+    ) -> Result<Self, MovingAverageError> {
+        const {
+            assert!(
+                size_of::<TCALC>() > size_of::<T>(),
+                "TCALC must be larger than T"
+            );
+        }
+        Self::check_alpha(alpha_num, alpha_den)?;
+        Ok(Self {
+            alpha_num,
+            alpha_den,
+            state: None,
+            sample: PhantomData,
+        })
+    }
+
+    /// The filter a failed check falls back to with the `defmt-checks`
+    /// feature. A factor of one always fits, as TCALC holds `T::MAX - T::MIN`.
+    pub(crate) const fn pass_through() -> Self {
+        Self {
+            alpha_num: TCALC::ONE,
+            alpha_den: TCALC::ONE,
+            state: None,
+            sample: PhantomData,
+        }
+    }
+
+    fn with_time_constant(tau: u32) -> Result<Self, MovingAverageError> {
+        let two = TCALC::ONE + TCALC::ONE;
+        let samples: usize = convert(tau, MovingAverageError::CountOverflow)?;
+        let double = samples
+            .checked_mul(2)
+            .ok_or(MovingAverageError::CountOverflow)?;
+        let den = convert(double + 1, MovingAverageError::CountOverflow)?;
+        // A tau of zero gives a factor of 2, which `with_alpha` rejects.
+        Self::with_alpha(two, den)
+    }
+
+    pub(crate) fn check_alpha(
+        alpha_num: TCALC,
+        alpha_den: TCALC,
+    ) -> Result<(), MovingAverageError> {
+        check!(
+            alpha_num > TCALC::ZERO && alpha_num <= alpha_den,
+            MovingAverageError::InvalidAlpha
+        );
+        let range = abs_diff(TCALC::from(T::MAX), TCALC::from(T::MIN));
+        let fits = range <= TCALC::MAX / alpha_num;
+        check!(fits, MovingAverageError::AlphaOverflow);
+        Ok(())
+    }
+}
+
+/// # Intent
+/// Updates with `average`, and reads with `current_average`.
+impl<T, TCALC> Filter<T> for Ewma<T, TCALC>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn update(&mut self, input: T) -> T {
+        self.average(input)
+    }
+
+    fn value(&self) -> Option<T> {
+        self.current_average()
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg_attr(
+    feature = "result-checks",
+    expect(clippy::expect_used, reason = "Desirable in tests")
+)]
+#[cfg(test)]
+mod tests {
+    use super::Ewma;
+    #[cfg(feature = "result-checks")]
+    use crate::MovingAverageError;
+
+    #[test]
+    fn given_step_when_average_then_close_gap_by_alpha() {
+        let mut sut = Ewma::<u16, u32>::new(1_u32, 2_u32);
+        assert_eq!(None, sut.current_average());
+        assert_eq!(0_u16, sut.average(0_u16));
+        assert_eq!(500_u16, sut.average(1000_u16));
+        assert_eq!(750_u16, sut.average(1000_u16));
+        assert_eq!(875_u16, sut.average(1000_u16));
+        assert_eq!(438_u16, sut.average(0_u16));
+        assert_eq!(Some(438_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_signed_samples_when_average_then_truncate_towards_zero_step() {
+        let mut sut = Ewma::<i16, i32>::new(1_i32, 3_i32);
+        let _ = sut.average(0_i16);
+        assert_eq!(-33_i16, sut.average(-100_i16));
+        assert_eq!(41_i16, sut.average(190_i16));
+    }
+
+    #[test]
+    fn given_time_constant_when_step_response_then_reach_63_percent_after_tau() {
+        for tau in [5_u32, 10_u32, 50_u32] {
+            let mut sut = Ewma::<u16, u32>::from_time_constant(tau);
+            let _ = sut.average(0_u16);
+            let mut response = 0_u16;
+            for _ in 0..tau {
+                response = sut.average(10_000_u16);
+            }
+            assert!(
+                (6221_u16..=6421_u16).contains(&response),
+                "Failed at {tau}: {response}"
+            );
+        }
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "alpha_num / alpha_den must be in (0, 1]")]
+    fn confirm_time_constant_is_non_zero() {
+        let _sut = Ewma::<u16, u32>::from_time_constant(0_u32);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "alpha_num / alpha_den must be in (0, 1]")]
+    fn confirm_alpha_is_at_most_one() {
+        let _sut = Ewma::<u16, u32>::new(3_u32, 2_u32);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "(T.max() - T.min()) * alpha_num must fit in TCALC")]
+    fn confirm_alpha_num_times_t_range_fits_in_tcalc() {
+        let _sut = Ewma::<u8, u16>::new(0x102_u16, 0x200_u16);
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_bad_factor_when_try_new_then_return_error() {
+        assert_eq!(
+            Some(MovingAverageError::InvalidAlpha),
+            Ewma::<u16, u32>::try_new(3_u32, 2_u32).err()
+        );
+        assert_eq!(
+            Some(MovingAverageError::AlphaOverflow),
+            Ewma::<u8, u16>::try_new(0x102_u16, 0x200_u16).err()
+        );
+        assert_eq!(
+            Some(MovingAverageError::InvalidAlpha),
+            Ewma::<u16, u32>::try_from_time_constant(0_u32).err()
+        );
+        let mut sut = Ewma::<u16, u32>::try_new(1_u32, 2_u32).expect("Valid factor");
+        let _ = sut.average(0_u16);
+        assert_eq!(500_u16, sut.average(1000_u16));
+    }
+
+    #[cfg(feature = "defmt-checks")]
+    #[test]
+    fn given_defmt_checks_when_factor_is_bad_then_pass_input_through() {
+        let mut sut = Ewma::<u16, u32>::new(3_u32, 2_u32);
+        let _ = sut.average(0_u16);
+        assert_eq!(1000_u16, sut.average(1000_u16));
+        let mut from_tau = Ewma::<u16, u32>::from_time_constant(0_u32);
+        let _ = from_tau.average(0_u16);
+        assert_eq!(1000_u16, from_tau.average(1000_u16));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    Accumulator, Ewma, MovingAverageError, Sample,
+    error::{convert, unwrap_check},
+};
+
+/// # Intent
+/// An exponential moving average with its smoothing factor fixed in the
+/// type, for O(1) state and no buffer. Each sample updates the average as
+/// `new = old + (input - old) * ALPHA_NUM / ALPHA_DEN` in TCALC, the
+/// fixed-point form of a factor between zero and one. The average starts
+/// at the first sample.
+///
+/// The step is truncated towards zero, as for `Ewma`, so the average can
+/// settle short of a constant input by up to `ALPHA_DEN / ALPHA_NUM`.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::ExponentialMovingAverage;
+///
+/// let mut sut = ExponentialMovingAverage::<u16, u32, 1, 4>::new();
+/// let _ = sut.average(100);
+///
+/// assert_eq!(125, sut.average(200));
+/// ```
+///
+/// ## The factor must be in (0, 1]
+///
+/// ```compile_fail
+/// use no_std_moving_average::ExponentialMovingAverage;
+/// let _sut = ExponentialMovingAverage::<u16, u32, 5, 4>::new();
+/// ```
+pub struct ExponentialMovingAverage<T, TCALC, const ALPHA_NUM: usize, const ALPHA_DEN: usize>(
+    Ewma<T, TCALC>,
+)
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>;
+
+/// # Panics
+/// Panics if the factor is zero or above one, compile-time assert.
+/// Panics if `ALPHA_NUM` or `ALPHA_DEN` won't fit in TCALC.
+/// Panics as `Ewma::new`, for the factor.
+impl<T, TCALC, const ALPHA_NUM: usize, const ALPHA_DEN: usize> Default
+    for ExponentialMovingAverage<T, TCALC, ALPHA_NUM, ALPHA_DEN>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn default() -> Self {
+        const {
+            assert!(
+                ALPHA_NUM > 0 && ALPHA_NUM <= ALPHA_DEN,
+                "ALPHA_NUM / ALPHA_DEN must be in (0, 1]"
+            );
+        }
+        let alpha_num = unwrap_check(convert(ALPHA_NUM, MovingAverageError::AlphaOverflow));
+        let alpha_den = unwrap_check(convert(ALPHA_DEN, MovingAverageError::AlphaOverflow));
+        Self(unwrap_check(Ewma::with_alpha(alpha_num, alpha_den)))
+    }
+}
+
+impl<T, TCALC, const ALPHA_NUM: usize, const ALPHA_DEN: usize>
+    ExponentialMovingAverage<T, TCALC, ALPHA_NUM, ALPHA_DEN>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Panics
+    /// Panics as `Ewma::average`, which should never occur.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        self.0.average(input)
+    }
+
+    /// # Intent
+    /// Returns the current average without inserting a sample, or `None`
+    /// if no samples have been recorded.
+    #[must_use]
+    pub fn current_average(&self) -> Option<T> {
+        self.0.current_average()
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::ExponentialMovingAverage;
+
+    #[test]
+    fn given_step_when_average_then_converge_as_hand_computed() {
+        let mut sut = ExponentialMovingAverage::<u16, u32, 1, 4>::new();
+        assert_eq!(None, sut.current_average());
+        let _ = sut.average(0_u16);
+        // 256 * (1 - (3/4)^k), each step truncated.
+        for expected in [64_u16, 112_u16, 148_u16, 175_u16, 195_u16] {
+            assert_eq!(expected, sut.average(256_u16));
+        }
+        assert_eq!(Some(195_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_truncated_steps_when_constant_input_then_settle_short_by_den_over_num() {
+        let mut sut = ExponentialMovingAverage::<i16, i32, 1, 4>::new();
+        let _ = sut.average(0_i16);
+        for _ in 0..64_usize {
+            let _ = sut.average(-100_i16);
+        }
+        assert_eq!(Some(-97_i16), sut.current_average());
+    }
+
+    #[test]
+    fn given_alpha_of_one_when_average_then_follow_input() {
+        let mut sut = ExponentialMovingAverage::<u8, u16, 3, 3>::new();
+        for val in [9_u8, 200_u8, 0_u8] {
+            assert_eq!(val, sut.average(val));
+        }
+    }
+}
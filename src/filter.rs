@@ -0,0 +1,12 @@
+/// # Intent
+/// A smoothing filter fed one sample at a time, so code can be written
+/// generic over the filtering strategy, e.g. to swap a simple moving
+/// average for an exponential one.
+pub trait Filter<T> {
+    /// Feeds `input` to the filter, returning the new filtered value.
+    fn update(&mut self, input: T) -> T;
+
+    /// Returns the filtered value without feeding a sample, or `None` if no
+    /// samples have been recorded.
+    fn value(&self) -> Option<T>;
+}
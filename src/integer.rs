@@ -0,0 +1,267 @@
+use core::{
+    fmt::Debug,
+    mem::size_of,
+    ops::{Add, Div, Mul, Sub},
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// # Intent
+/// Describes the properties of the primitive integer types that the
+/// filters need beyond the arithmetic operator traits.
+///
+/// This trait is sealed, and is implemented for all primitive integers.
+pub trait Integer: sealed::Sealed + Sized + Clone + Copy {
+    /// The value zero.
+    const ZERO: Self;
+    /// The value one.
+    const ONE: Self;
+    /// The smallest value of the type.
+    const MIN: Self;
+    /// The largest value of the type.
+    const MAX: Self;
+    /// True if the type can represent negative values.
+    const SIGNED: bool;
+
+    /// Writes the little-endian bytes of the value to `out`, which must be
+    /// exactly `size_of::<Self>()` long.
+    fn write_le_bytes(self, out: &mut [u8]);
+
+    /// Adds `rhs`, clamping at the bounds of the type.
+    #[must_use]
+    fn saturating_add(self, rhs: Self) -> Self;
+
+    /// Subtracts `rhs`, clamping at the bounds of the type.
+    #[must_use]
+    fn saturating_sub(self, rhs: Self) -> Self;
+
+    /// Multiplies by `rhs`, clamping at the bounds of the type.
+    #[must_use]
+    fn saturating_mul(self, rhs: Self) -> Self;
+
+    /// Adds `rhs`, or returns `None` on overflow.
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    /// Subtracts `rhs`, or returns `None` on overflow.
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// Multiplies by `rhs`, or returns `None` on overflow.
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+}
+
+/// # Intent
+/// The bounds a sample type needs to be averaged in the calculation type
+/// `TCALC`, gathered so the filters can write `T: Sample<TCALC>`.
+///
+/// This trait is sealed through `Integer`, and is implemented for every
+/// primitive integer that can be narrowed back from `TCALC`.
+pub trait Sample<TCALC>:
+    Sized + PartialEq + TryFrom<TCALC, Error: Debug> + Clone + Copy + Integer
+{
+}
+
+impl<T, TCALC> Sample<TCALC> for T where
+    T: Sized + PartialEq + TryFrom<TCALC, Error: Debug> + Clone + Copy + Integer
+{
+}
+
+/// # Intent
+/// The bounds a calculation type needs to accumulate samples of type `T`,
+/// gathered so the filters can write `TCALC: Accumulator<T>`.
+///
+/// This trait is sealed through `Integer`, and is implemented for every
+/// primitive integer that `T` widens into.
+pub trait Accumulator<T>:
+    Sized
+    + Add<Self, Output = Self>
+    + Sub<Self, Output = Self>
+    + Div<Output = Self>
+    + Mul<Output = Self>
+    + PartialEq
+    + PartialOrd
+    + From<T>
+    + TryFrom<usize, Error: Debug>
+    + Clone
+    + Copy
+    + Integer
+{
+}
+
+impl<T, TCALC> Accumulator<T> for TCALC where
+    TCALC: Sized
+        + Add<TCALC, Output = TCALC>
+        + Sub<TCALC, Output = TCALC>
+        + Div<Output = TCALC>
+        + Mul<Output = TCALC>
+        + PartialEq
+        + PartialOrd
+        + From<T>
+        + TryFrom<usize, Error: Debug>
+        + Clone
+        + Copy
+        + Integer
+{
+}
+
+/// # Intent
+/// The depth N as a value of the type, so filters can be built in a const
+/// context, where `TryFrom` can't be called.
+///
+/// This trait is sealed through `Integer`, and is implemented for all
+/// primitive integers. Using `DEPTH` fails to compile if N won't fit.
+pub trait Depth<const N: usize>: Integer {
+    /// N, converted to the type.
+    const DEPTH: Self;
+}
+
+macro_rules! impl_integer {
+    ($signed:expr => $($t:ty),+) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl<const N: usize> Depth<N> for $t {
+                // Copied byte by byte, as `as` would trip the cast lints
+                // differently for each type and target.
+                const DEPTH: Self = {
+                    assert!(
+                        usize::BITS - N.leading_zeros() <= magnitude_bits::<$t>(),
+                        "N must fit in TCALC"
+                    );
+                    let source = N.to_le_bytes();
+                    let mut bytes = [0_u8; size_of::<$t>()];
+                    let mut i = 0;
+                    while i < bytes.len() && i < source.len() {
+                        bytes[i] = source[i];
+                        i += 1;
+                    }
+                    <$t>::from_le_bytes(bytes)
+                };
+            }
+
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+                const SIGNED: bool = $signed;
+
+                fn write_le_bytes(self, out: &mut [u8]) {
+                    out.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+
+                fn saturating_sub(self, rhs: Self) -> Self {
+                    <$t>::saturating_sub(self, rhs)
+                }
+
+                fn saturating_mul(self, rhs: Self) -> Self {
+                    <$t>::saturating_mul(self, rhs)
+                }
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+            }
+        )+
+    };
+}
+
+impl_integer!(false => u8, u16, u32, u64, u128, usize);
+impl_integer!(true => i8, i16, i32, i64, i128, isize);
+
+/// Returns true if a sum of `depth` values of T fits in TCALC.
+/// Only the positive range of a signed TCALC counts.
+pub(crate) const fn fits_in_accumulator<T: Integer, TCALC: Integer>(depth: usize) -> bool {
+    magnitude_bits::<T>() + ceil_log2(depth) <= magnitude_bits::<TCALC>()
+}
+
+/// Returns true if a sum of `depth` squared differences of T values fits in
+/// TCALC, as needed by the variance. A difference spans the full range of
+/// T, so its square takes twice the bits of T.
+pub(crate) const fn fits_squares_in_accumulator<T: Integer, TCALC: Integer>(depth: usize) -> bool {
+    2 * bits::<T>() + ceil_log2(depth) <= magnitude_bits::<TCALC>()
+}
+
+// The bounds are compared as bit widths rather than computed with `pow`,
+// which would overflow u128 for the widest types, and in `const fn` so the
+// filters can check them at compile time.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "no size_of return bigger than u32"
+)]
+const fn bits<V: Integer>() -> u32 {
+    (size_of::<V>() as u32) * 8
+}
+
+const fn magnitude_bits<V: Integer>() -> u32 {
+    bits::<V>() - V::SIGNED as u32
+}
+
+const fn ceil_log2(depth: usize) -> u32 {
+    usize::BITS - depth.saturating_sub(1).leading_zeros()
+}
+
+/// Returns the distance between `a` and `b`, without going negative.
+pub(crate) fn abs_diff<TCALC>(a: TCALC, b: TCALC) -> TCALC
+where
+    TCALC: Sub<TCALC, Output = TCALC> + PartialOrd,
+{
+    if a < b { b - a } else { a - b }
+}
+
+/// Moves `state` towards `target` by `alpha_num / alpha_den` of the gap,
+/// the step truncated towards zero, as one update of a one-pole IIR filter.
+pub(crate) fn step_toward<TCALC>(
+    state: TCALC,
+    target: TCALC,
+    alpha_num: TCALC,
+    alpha_den: TCALC,
+) -> TCALC
+where
+    TCALC: Add<TCALC, Output = TCALC>
+        + Sub<TCALC, Output = TCALC>
+        + Mul<Output = TCALC>
+        + Div<Output = TCALC>
+        + PartialOrd
+        + Copy,
+{
+    let step = abs_diff(target, state) * alpha_num / alpha_den;
+    if target < state {
+        state - step
+    } else {
+        state + step
+    }
+}
+
+/// Returns the integer square root of a non-negative `value`, rounded down.
+/// Newton's method, starting from `value / 2 + 1` which is never below the
+/// root, so the iterates fall monotonically and the sums can't overflow.
+pub(crate) fn isqrt<TCALC>(value: TCALC) -> TCALC
+where
+    TCALC: Add<TCALC, Output = TCALC> + Div<Output = TCALC> + PartialOrd + Integer,
+{
+    let two = TCALC::ONE + TCALC::ONE;
+    if value < two {
+        return value;
+    }
+    let mut root = value / two + TCALC::ONE;
+    let mut next = (root + value / root) / two;
+    while next < root {
+        root = next;
+        next = (root + value / root) / two;
+    }
+    root
+}
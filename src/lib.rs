@@ -199,6 +199,13 @@
 #![deny(clippy::string_add)]
 #![deny(clippy::string_lit_chars_any)]
 #![deny(clippy::string_slice)]
+#![cfg_attr(
+    clippy,
+    expect(
+        renamed_and_removed_lints,
+        reason = "clippy::string_to_string is removed from current clippy, and is kept for the toolchains that still have it."
+    )
+)]
 #![deny(clippy::string_to_string)]
 #![deny(clippy::suspicious_xor_used_as_pow)]
 #![deny(clippy::tests_outside_test_module)]
@@ -224,9 +231,114 @@
 #![deny(clippy::verbose_file_reads)]
 #![deny(clippy::wildcard_enum_match_arm)]
 
+/// Copyright ©2025 Kelvin Systems
+mod adaptive_window_average;
+/// Copyright ©2025 Kelvin Systems
+mod baseline_corrected_average;
+/// Copyright ©2025 Kelvin Systems
+mod borrowed_moving_average;
+/// Copyright ©2025 Kelvin Systems
+mod centered_average;
+/// Copyright ©2025 Kelvin Systems
+mod coefficients;
+/// Copyright ©2025 Kelvin Systems
+mod const_moving_average;
+/// Copyright ©2025 Kelvin Systems
+mod crossover;
+/// Copyright ©2025 Kelvin Systems
+mod error;
+/// Copyright ©2025 Kelvin Systems
+mod ewma;
+/// Copyright ©2025 Kelvin Systems
+mod exponential_moving_average;
+/// Copyright ©2025 Kelvin Systems
+mod filter;
+/// Copyright ©2025 Kelvin Systems
+mod integer;
 /// Copyright ©2025 Kelvin Systems
 mod moving_average;
+/// Copyright ©2025 Kelvin Systems
+mod smoothed_derivative;
+/// Copyright ©2025 Kelvin Systems
+mod time_weighted_moving_average;
+/// Copyright ©2025 Kelvin Systems
+mod weighted_moving_average;
 
 #[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
 #[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
-pub use moving_average::MovingAverage;
+pub use adaptive_window_average::AdaptiveWindowAverage;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use baseline_corrected_average::BaselineCorrectedAverage;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use borrowed_moving_average::BorrowedMovingAverage;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use centered_average::centered_average_into;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use coefficients::Coefficients;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use const_moving_average::ConstMovingAverage;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use crossover::{CrossDirection, Crossover};
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use error::MovingAverageError;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use ewma::Ewma;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use exponential_moving_average::ExponentialMovingAverage;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use filter::Filter;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use integer::{Accumulator, Depth, Integer, Sample};
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use moving_average::{
+    Diagnostics, Drain, Extremes, MissingPolicy, MovingAverage, Quality, Summary, WarmedAverage,
+};
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use smoothed_derivative::SmoothedDerivative;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use time_weighted_moving_average::TimeWeightedMovingAverage;
+#[expect(clippy::useless_attribute, reason = "Working around clippy bug")]
+#[expect(clippy::pub_use, reason = "Exporting without exposing file structure")]
+pub use weighted_moving_average::WeightedMovingAverage;
+
+/// A defmt logger for the tests, counting the bytes written so that tests
+/// can confirm a `Format` impl produced output.
+#[cfg(all(test, feature = "defmt"))]
+pub(crate) mod test_logger {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    pub(crate) static BYTES_WRITTEN: AtomicUsize = AtomicUsize::new(0);
+
+    #[defmt::global_logger]
+    struct TestLogger;
+
+    // SAFETY: The logger only counts bytes in an atomic, so holds no state
+    // that concurrent or reentrant use could corrupt.
+    unsafe impl defmt::Logger for TestLogger {
+        fn acquire() {}
+
+        unsafe fn flush() {}
+
+        unsafe fn release() {}
+
+        unsafe fn write(bytes: &[u8]) {
+            BYTES_WRITTEN.fetch_add(bytes.len(), Ordering::Relaxed);
+        }
+    }
+
+    defmt::timestamp!("");
+}
@@ -1,10 +1,18 @@
 use core::{
     cmp::PartialOrd,
-    fmt::Debug,
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
     mem::size_of,
-    ops::{Add, Div, Mul, Sub},
 };
-use heapless::HistoryBuffer;
+use heapless::{HistoryBuffer, Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+use crate::{
+    Accumulator, Coefficients, Depth, Ewma, Filter, MovingAverageError, Sample,
+    error::{check, convert, finish_check, unwrap_check},
+    integer::{abs_diff, fits_in_accumulator, fits_squares_in_accumulator, isqrt, step_toward},
+};
 
 /// # Intent
 /// Creates a Moving Average filter for integer values,
@@ -19,8 +27,20 @@ use heapless::HistoryBuffer;
 /// * T - the data type being averaged
 /// * TCALC - a larger data type for calculating the average
 ///   * Must fit the value `N * T::MAX`
+///   * Only the positive range of a signed TCALC counts, so `u16` in an
+///     `i32` allows half the depth of `u16` in a `u32`
 /// * N - the depth of the average
 ///   * Must be non-zero
+/// * PREFILL - how the window starts, `true` by default
+///   * `true` stuffs the entire window with the first sample
+///   * `false` never writes anything but real samples, and divides by the
+///     number of samples seen until the window is full, so startup is
+///     fully explicit
+/// * SATURATING - how the running sum overflows, `false` by default
+///   * `false` keeps the sum exact, relying on the capacity check
+///   * `true` skips the capacity check, and clamps the sum at the bounds
+///     of TCALC instead. Once clamped, the sum no longer tracks the window
+///     exactly, but the average can't wrap
 ///
 /// # Example
 ///
@@ -79,228 +99,2561 @@ use heapless::HistoryBuffer;
 ///
 /// ## N * `T::MAX` must fit in TCALC
 ///
-/// ```should_panic
+/// Unless the `unchecked-capacity` feature is enabled.
+///
+#[cfg_attr(not(feature = "unchecked-capacity"), doc = "```compile_fail")]
+#[cfg_attr(feature = "unchecked-capacity", doc = "```ignore")]
 /// use no_std_moving_average::MovingAverage;
 /// let _sut = MovingAverage::<u8, u16, 512>::new();
 /// ```
 ///
-pub struct MovingAverage<T, TCALC, const N: usize>
-where
-    T: Sized + PartialEq + TryFrom<TCALC, Error: Debug> + Clone + Copy,
-    TCALC: Sized
-        + Add<TCALC, Output = TCALC>
-        + Sub<TCALC, Output = TCALC>
-        + Div<Output = TCALC>
-        + Mul<Output = TCALC>
-        + PartialEq
-        + PartialOrd
-        + From<T>
-        + TryFrom<usize, Error: Debug>
-        + Clone
-        + Copy,
+/// Only the positive range of a signed TCALC counts.
+///
+#[cfg_attr(not(feature = "unchecked-capacity"), doc = "```compile_fail")]
+#[cfg_attr(feature = "unchecked-capacity", doc = "```ignore")]
+/// use no_std_moving_average::MovingAverage;
+/// let _sut = MovingAverage::<u16, i32, 0x1_0000>::new();
+/// ```
+///
+#[derive(Clone)]
+pub struct MovingAverage<
+    T,
+    TCALC,
+    const N: usize,
+    const PREFILL: bool = true,
+    const SATURATING: bool = false,
+> where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
 {
     num: TCALC,
     sum: Option<TCALC>,
     buffer: HistoryBuffer<T, N>,
+    sentinel: Option<T>,
+    output_scale: Option<(TCALC, TCALC)>,
+    samples_since_change: u32,
+    last_output: Option<T>,
+    iir_alpha: Option<(TCALC, TCALC)>,
+    iir_state: Option<TCALC>,
+    observed: usize,
+    ramp: Option<Ramp<TCALC>>,
+    missing_policy: MissingPolicy,
+    active: usize,
+}
+
+/// # Intent
+/// What `MovingAverage::tick_missing` inserts in place of a missing
+/// sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingPolicy {
+    /// Repeat the newest sample.
+    Hold,
+    /// Continue the slope of the two newest samples, saturating at the
+    /// range of T.
+    Extrapolate,
+}
+
+/// The progress of a `transition_to` ramp, in TCALC.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Ramp<TCALC> {
+    start: TCALC,
+    target: TCALC,
+    taken: TCALC,
+    steps: TCALC,
+}
+
+/// How the output stage rounds the scaled average to T.
+#[derive(Clone, Copy)]
+enum Rounding {
+    /// Truncated towards zero, as integer division does.
+    TowardZero,
+    /// To the nearest integer, with halves away from zero.
+    Nearest,
+    /// Towards negative infinity.
+    Floor,
+}
+
+/// # Intent
+/// The mean, minimum, and maximum of the window, as returned by
+/// `MovingAverage::summary`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary<T> {
+    pub mean: T,
+    pub min: T,
+    pub max: T,
+}
+
+/// # Intent
+/// The minimum and maximum of the window with their ages, as returned by
+/// `MovingAverage::extremes_with_position`. An age of 0 is the newest
+/// sample, and `len - 1` the oldest, next to be evicted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extremes<T> {
+    pub min: T,
+    pub min_age: usize,
+    pub max: T,
+    pub max_age: usize,
+}
+
+/// # Intent
+/// A snapshot of the filter for logging, as returned by
+/// `MovingAverage::diagnostics`. The variance is the integer population
+/// variance of the window, about `average`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics<T, TCALC> {
+    pub average: T,
+    pub sum: TCALC,
+    pub count: usize,
+    pub min: T,
+    pub max: T,
+    pub variance: TCALC,
+}
+
+/// # Intent
+/// How far an average can be trusted, as returned by
+/// `MovingAverage::average_status`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quality {
+    /// The window isn't yet full of real samples, so the average is still
+    /// biased by the warm-up.
+    Warming,
+    /// The average is over a window full of real samples.
+    Valid,
+    /// The average didn't fit in T, and was saturated to its range by the
+    /// `saturating-narrow` feature.
+    Saturated,
+}
+
+/// # Intent
+/// The samples taken out of a filter by `MovingAverage::drain`, oldest to
+/// newest. They are a copy, so the filter is already reset and can be fed
+/// while they are read.
+#[derive(Clone, Debug)]
+pub struct Drain<T, const N: usize> {
+    window: [T; N],
+    next: usize,
+    len: usize,
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "The provided adaptors are sufficient"
+)]
+impl<T: Copy, const N: usize> Iterator for Drain<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let value = self.window[..self.len].get(self.next).copied()?;
+        self.next += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.next;
+        (remaining, Some(remaining))
+    }
 }
 
 /// # Panics
 /// Panics if TCALC not larger than T, compile-time assert.
 /// Panics if N is zero, compile-time assert.
+/// Panics if N * `T::MAX` won't fit in TCALC, without SATURATING or the
+/// `unchecked-capacity` feature, compile-time assert.
 /// : These panics should never occur due to compile-time assert checks.
 /// Panics if unable to convert from usize to TCALC.
-/// Panics if N * `T::MAX` won't fit in TCALC.
-/// : These panics happen at allocation time, so should be found predictably.
-#[expect(clippy::unwrap_used, reason = "Made safe by compile-time asserts")]
-impl<T, TCALC, const N: usize> Default for MovingAverage<T, TCALC, N>
+/// : This panic happens at allocation time, so should be found predictably.
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Default
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
 where
-    T: Sized + PartialEq + TryFrom<TCALC, Error: Debug> + Clone + Copy,
-    TCALC: Sized
-        + Add<TCALC, Output = TCALC>
-        + Sub<TCALC, Output = TCALC>
-        + Div<Output = TCALC>
-        + Mul<Output = TCALC>
-        + PartialEq
-        + PartialOrd
-        + From<T>
-        + TryFrom<usize, Error: Debug>
-        + Clone
-        + Copy,
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
 {
-    #[expect(
-        clippy::cast_possible_truncation,
-        reason = "no size_of return bigger than u32"
-    )]
     fn default() -> Self {
-        const {
-            assert!(
-                size_of::<TCALC>() > size_of::<T>(),
-                "TCALC must be larger than T"
-            );
-            assert!(N > 0, "N must be non-zero");
+        Self::with_checked_capacity(unwrap_check(convert(
+            N,
+            MovingAverageError::CapacityOverflow,
+        )))
+    }
+}
+
+/// # Intent
+/// Prints the depth of the active window, the current average, and the
+/// window from oldest to newest, rather than the ring buffer in its
+/// physical order.
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Debug
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC> + Debug,
+    TCALC: Accumulator<T>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (window, len) = self.ordered_window();
+        f.debug_struct("MovingAverage")
+            .field("depth", &self.active_window())
+            .field("average", &self.current_average())
+            .field("window", &&window[..len])
+            .finish()
+    }
+}
+
+/// # Intent
+/// Prints the current average, the number of real samples, and the depth
+/// of the active window, e.g. `avg=Some(30) n=3/8`, compactly for an RTT
+/// link.
+#[cfg(feature = "defmt")]
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "The provided methods are hidden defmt helpers"
+)]
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> defmt::Format
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC> + defmt::Format,
+    TCALC: Accumulator<T>,
+{
+    fn format(&self, fmt: defmt::Formatter<'_>) {
+        defmt::write!(
+            fmt,
+            "avg={} n={}/{}",
+            self.current_average(),
+            self.len(),
+            self.active_window()
+        );
+    }
+}
+
+/// # Intent
+/// Compares the depth, the running sum, and the window from oldest to
+/// newest, so filters fed the same sequence compare equal regardless of
+/// where the ring buffer's write position happens to be. The settings that
+/// change what `average` returns are compared too: the sentinel, output
+/// scale, active window, ramp in progress, and missing-sample policy, and
+/// the IIR factor of `average_then_iir`.
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "The provided ne is sufficient"
+)]
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> PartialEq
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.num == other.num
+            && self.sum == other.sum
+            && self.sentinel == other.sentinel
+            && self.output_scale == other.output_scale
+            && self.active == other.active
+            && self.ramp == other.ramp
+            && self.missing_policy == other.missing_policy
+            && self.iir_alpha == other.iir_alpha
+            && self
+                .buffer
+                .oldest_ordered()
+                .eq(other.buffer.oldest_ordered())
+    }
+}
+
+/// # Intent
+/// The comparison is total, as for the primitive integers compared, and
+/// consistent with `Hash`, which hashes the same state.
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "The provided methods are hidden compiler helpers"
+)]
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Eq
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC> + Eq,
+    TCALC: Accumulator<T> + Eq,
+{
+}
+
+/// # Intent
+/// Hashes the depth, the running sum, the settings compared by `PartialEq`,
+/// and the window from oldest to newest, so filters fed the same sequence
+/// hash equally regardless of where the ring buffer's write position
+/// happens to be.
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "The provided hash_slice is sufficient"
+)]
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Hash
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC> + Hash,
+    TCALC: Accumulator<T> + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.num.hash(state);
+        self.sum.hash(state);
+        self.sentinel.hash(state);
+        self.output_scale.hash(state);
+        self.active.hash(state);
+        self.iir_alpha.hash(state);
+        self.ramp.hash(state);
+        self.missing_policy.hash(state);
+        for value in self.buffer.oldest_ordered() {
+            value.hash(state);
+        }
+    }
+}
+
+/// The state of a `MovingAverage` that is persisted by serde. A ramp or
+/// cascaded IIR in progress isn't kept, and starts over after a restore.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "MovingAverage")]
+struct Persisted<T, TCALC, const N: usize> {
+    num: TCALC,
+    sum: Option<TCALC>,
+    window: Vec<T, N>,
+    observed: usize,
+    active: usize,
+    sentinel: Option<T>,
+    output_scale: Option<(TCALC, TCALC)>,
+    missing_policy: MissingPolicy,
+    iir_alpha: Option<(TCALC, TCALC)>,
+}
+
+/// # Intent
+/// Serializes the depth, the running sum, the stored window from oldest to
+/// newest, and the settings, so a filter can be restored after a reboot
+/// without a cold start.
+#[cfg(feature = "serde")]
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Serialize
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC> + Serialize,
+    TCALC: Accumulator<T> + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Persisted {
+            num: self.num,
+            sum: self.sum,
+            window: self.buffer.oldest_ordered().copied().collect::<Vec<T, N>>(),
+            observed: self.observed,
+            active: self.active,
+            sentinel: self.sentinel,
+            output_scale: self.output_scale,
+            missing_policy: self.missing_policy,
+            iir_alpha: self.iir_alpha,
         }
-        debug_assert!(
-            (2_u128.pow((size_of::<T>() as u32) * 8) * u128::try_from(N).unwrap())
-                <= 2_u128.pow((size_of::<TCALC>() as u32) * 8),
-            "N * T.max() must fit in TCALC"
+        .serialize(serializer)
+    }
+}
+
+/// # Intent
+/// Restores a filter serialized by `Serialize`, checking that the state is
+/// consistent, so a corrupted record gives an error rather than a filter
+/// that panics later.
+///
+/// # Errors
+/// Returns the serde error for a `MovingAverageError`:
+/// `IncompleteWindow` if a PREFILL filter's window is neither empty nor
+/// full, `InvalidLength` for an active window outside `1..=N`,
+/// `InconsistentState` if the depth, sum or sample count doesn't match the
+/// window, the output scale errors of `new_with_output_scale`, and the IIR
+/// factor errors of `enable_iir`.
+#[cfg(feature = "serde")]
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "In-place deserialization would skip the validation"
+)]
+impl<'de, T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Deserialize<'de>
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC> + Deserialize<'de>,
+    TCALC: Accumulator<T> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let persisted = Persisted::deserialize(deserializer)?;
+        Self::from_persisted(persisted).map_err(D::Error::custom)
+    }
+}
+
+/// # Intent
+/// Builds a filter whose window is the contents of `samples`, oldest
+/// first, for samples collected elsewhere.
+///
+/// # Errors
+/// Returns `IncompleteWindow` if `samples` isn't full and PREFILL is true.
+/// Without PREFILL a partial, or empty, `samples` gives a partially
+/// filled window.
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> TryFrom<Vec<T, N>>
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    type Error = MovingAverageError;
+
+    fn try_from(samples: Vec<T, N>) -> Result<Self, Self::Error> {
+        check!(
+            !PREFILL || samples.is_full(),
+            MovingAverageError::IncompleteWindow
         );
-        Self {
-            num: TCALC::try_from(N).unwrap(),
-            sum: None,
-            buffer: HistoryBuffer::new(),
+        let mut sut = Self::new();
+        if !samples.is_empty() {
+            let mut sum = TCALC::ZERO;
+            sut.observed = samples.len();
+            for value in samples {
+                sut.buffer.write(value);
+                sum = sum + TCALC::from(value);
+            }
+            sut.sum = Some(sum);
+        }
+        Ok(sut)
+    }
+}
+
+/// # Intent
+/// Every sample in the window has a weight of one. Without PREFILL, the
+/// positions not yet filled have a weight of zero, as do the positions
+/// beyond a shorter active window.
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Coefficients<TCALC, N>
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn coefficients(&self) -> [TCALC; N] {
+        let mut coefficients = [TCALC::ZERO; N];
+        for coefficient in coefficients.iter_mut().take(self.window_len()) {
+            *coefficient = TCALC::ONE;
         }
+        coefficients
+    }
+}
+
+/// # Intent
+/// Updates with `average`, and reads with `current_average`.
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool> Filter<T>
+    for MovingAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn update(&mut self, input: T) -> T {
+        self.average(input)
+    }
+
+    fn value(&self) -> Option<T> {
+        self.current_average()
     }
 }
 
-impl<T, TCALC, const N: usize> MovingAverage<T, TCALC, N>
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool>
+    MovingAverage<T, TCALC, N, PREFILL, SATURATING>
 where
-    T: Sized + PartialEq + TryFrom<TCALC, Error: Debug> + Clone + Copy,
-    TCALC: Sized
-        + Add<TCALC, Output = TCALC>
-        + Sub<TCALC, Output = TCALC>
-        + Div<Output = TCALC>
-        + Mul<Output = TCALC>
-        + PartialEq
-        + PartialOrd
-        + From<T>
-        + TryFrom<usize, Error: Debug>
-        + Clone
-        + Copy,
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
 {
     #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// # Intent
+    /// Creates a filter like `new`, in a const context, e.g. for a `static`
+    /// filter without lazy initialization.
+    ///
+    /// ```rust
+    /// use no_std_moving_average::MovingAverage;
+    ///
+    /// static TEMPERATURE: MovingAverage<u16, u32, 8> = MovingAverage::new_const();
+    /// assert!(TEMPERATURE.is_empty());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics as `new`, compile-time asserts.
+    /// Panics if N won't fit in TCALC, compile-time assert.
+    #[must_use]
+    pub const fn new_const() -> Self
+    where
+        TCALC: Depth<N>,
+    {
+        Self::with_checked_capacity(TCALC::DEPTH)
+    }
+
+    /// # Intent
+    /// Creates a filter like `new`, but reports a depth whose sum won't fit
+    /// in TCALC as an error instead of panicking, in every build.
+    ///
+    /// # Errors
+    /// Returns `CapacityOverflow` if N * `T::MAX` won't fit in TCALC. With
+    /// SATURATING the sum is allowed to clamp, so this never errors.
+    pub fn try_new() -> Result<Self, MovingAverageError> {
+        if !SATURATING {
+            Self::check_capacity()?;
+        }
+        let num = convert(N, MovingAverageError::CapacityOverflow)?;
+        Ok(Self::with_num(num))
+    }
+
+    /// # Intent
+    /// Creates a filter whose window is already full of `value`, so the
+    /// N-iteration prefill happens here rather than in the first `average`.
+    /// No `average` call after construction performs the prefill loop,
+    /// giving every call the same cost, e.g. inside an ISR.
+    #[must_use]
+    pub fn new_preloaded(value: T) -> Self {
+        let mut sut = Self::default();
+        sut.fill(value);
+        sut
+    }
+
+    /// # Intent
+    /// Creates a filter warmed to a known starting point, e.g. room
+    /// temperature, instead of to the first real sample. The window holds
+    /// `value` N times, and each real sample slides one copy out.
+    ///
+    /// This is `new_preloaded`, named for the seed rather than the cost.
+    #[must_use]
+    pub fn with_initial(value: T) -> Self {
+        Self::new_preloaded(value)
+    }
+
+    /// # Intent
+    /// Creates a filter in the state it would have after averaging
+    /// `samples` one at a time, oldest first, e.g. to restart a control
+    /// loop warmed with its last readings.
+    ///
+    /// Only the newest N samples of a longer slice are kept. A shorter
+    /// slice behaves as fed: with PREFILL the oldest sample pads the rest
+    /// of the window, and without PREFILL the window stays partially full.
+    /// An empty slice gives a new filter.
+    #[must_use]
+    pub fn from_slice(samples: &[T]) -> Self {
+        let mut sut = Self::new();
+        for &value in &samples[samples.len().saturating_sub(N)..] {
+            let prev_sum = sut.get_or_init_and_get_sum(value);
+            let remove = sut.insert_new_value_pop_oldest_value(value);
+            sut.create_average(TCALC::from(value), prev_sum, remove, Rounding::TowardZero);
+        }
+        sut
+    }
+
+    /// # Intent
+    /// Creates a filter that ignores samples equal to `sentinel`, for
+    /// sources that report a "no data" value such as `0xFFFF`.
+    ///
+    /// Sentinel samples are not inserted, and `average` returns the current
+    /// average unchanged. Before any real sample, it returns the sentinel.
+    #[must_use]
+    pub fn new_with_sentinel(sentinel: T) -> Self {
+        Self {
+            sentinel: Some(sentinel),
+            ..Self::default()
+        }
+    }
+
+    /// # Intent
+    /// Creates a filter whose averages are scaled by
+    /// `scale_num / scale_den`, calculated as
+    /// `(sum * scale_num) / (N * scale_den)` so the multiply happens before
+    /// the division and no precision is lost to it.
+    ///
+    /// This needs extra TCALC headroom: `N * T::MAX * scale_num` and
+    /// `N * scale_den` must both fit in TCALC. The scaled average must also
+    /// fit in T.
+    ///
+    /// # Panics
+    /// Panics if `scale_num` or `scale_den` is zero or negative.
+    /// Panics if either product above won't fit in TCALC.
+    /// : These panics happen at allocation time, so should be found predictably.
+    /// With the `defmt-checks` feature, logs the error and returns an
+    /// unscaled filter instead.
+    #[must_use]
+    pub fn new_with_output_scale(scale_num: TCALC, scale_den: TCALC) -> Self {
+        finish_check(Self::with_output_scale(scale_num, scale_den), Self::new)
+    }
+
+    /// # Intent
+    /// Creates a filter whose averages are scaled by
+    /// `scale_num / scale_den`, as `new_with_output_scale` does, for
+    /// callers that handle a bad scale themselves.
+    ///
+    /// # Errors
+    /// Returns `ZeroScale` if `scale_num` or `scale_den` is zero,
+    /// `NegativeScale` if either is negative, or `ScaleOverflow` if the
+    /// scaled sum won't fit in TCALC.
+    #[cfg(feature = "result-checks")]
+    pub fn try_new_with_output_scale(
+        scale_num: TCALC,
+        scale_den: TCALC,
+    ) -> Result<Self, MovingAverageError> {
+        Self::with_output_scale(scale_num, scale_den)
+    }
+
     /// # Panics
     /// Panics if unable to convert from TCALC to T.
     /// This panic should never occur due to compile-time assert checks.
+    /// With the `saturating-narrow` feature, the average saturates to the
+    /// range of T instead.
     #[must_use]
     pub fn average(&mut self, input: T) -> T {
-        let new_value = TCALC::from(input);
-        let prev_sum = self.get_or_init_and_get_sum(input);
-        let remove = self.insert_new_value_pop_oldest_value(input);
-        self.create_average(new_value, prev_sum, remove)
+        self.average_by(input, Rounding::TowardZero)
     }
 
-    fn get_or_init_and_get_sum(&mut self, input: T) -> TCALC {
-        let new_value = TCALC::from(input);
-        if let Some(sum) = self.sum {
-            sum
+    /// # Intent
+    /// Averages `input` like `average`, also returning the sample it pushed
+    /// out of the window, for the caller's own bookkeeping.
+    ///
+    /// The evicted sample is `None` until the window is full of real samples,
+    /// as only copies from a fill are pushed out before then, and when a
+    /// sentinel `input` is skipped.
+    #[must_use]
+    pub fn average_with_evicted(&mut self, input: T) -> (T, Option<T>) {
+        let evicted = if self.is_full() && self.sentinel != Some(input) {
+            self.oldest()
         } else {
-            for _ in 0..N {
-                self.buffer.write(input);
-            }
-            self.num * new_value
+            None
+        };
+        (self.average(input), evicted)
+    }
+
+    /// # Intent
+    /// Averages `input` like `average`, but returns `None` instead of
+    /// overflowing, for type parameters that are only usually safe, e.g. an
+    /// undersized TCALC allowed by SATURATING or `unchecked-capacity`.
+    ///
+    /// Every step of the new sum is checked, and so is narrowing the new
+    /// average to T. On `None` the filter is left unchanged, and with
+    /// SATURATING the overflow is reported rather than clamped.
+    #[must_use]
+    pub fn average_checked(&mut self, input: T) -> Option<T> {
+        if self.sentinel != Some(input) {
+            let value = TCALC::from(input);
+            let (prev_sum, remove) = match self.sum {
+                Some(sum) => (sum, self.get_remove_value()),
+                None if PREFILL => (self.num.checked_mul(value)?, value),
+                None => (TCALC::ZERO, TCALC::ZERO),
+            };
+            let new_sum = prev_sum.checked_sub(remove)?.checked_add(value)?;
+            let divisor = if PREFILL || self.buffer.len() + 1 >= self.active {
+                self.num
+            } else {
+                TCALC::try_from(self.buffer.len() + 1).ok()?
+            };
+            let (numerator, denominator) = self.scaled_fraction_over(new_sum, divisor);
+            T::try_from(numerator / denominator).ok()?;
         }
+        Some(self.average(input))
     }
 
-    fn insert_new_value_pop_oldest_value(&mut self, input: T) -> TCALC {
-        let remove = self.get_remove_value();
-        self.buffer.write(input);
-        remove
+    /// # Intent
+    /// Averages `input` like `average`, but never overflows or panics on
+    /// pathological inputs, such as a signed sum pushed past an undersized
+    /// TCALC or a scaled output outside T.
+    ///
+    /// Where `average` would overflow TCALC, `sum - oldest + input` here
+    /// clamps at the bounds of TCALC. Once clamped, the sum no longer
+    /// tracks the window exactly, until samples bring it back in range.
+    /// Where `average` would panic converting to T, this returns `T::MAX`
+    /// or `T::MIN` instead, as the `saturating-narrow` feature does. The
+    /// first sample's fill is exact, as construction checked it fits.
+    #[must_use]
+    pub fn average_saturating(&mut self, input: T) -> T {
+        let average = if self.sentinel == Some(input) {
+            self.sum
+                .map_or(input, |sum| Self::saturate(self.scaled_average(sum)))
+        } else {
+            let new_value = TCALC::from(input);
+            let prev_sum = self.get_or_init_and_get_sum(input);
+            let remove = self.insert_new_value_pop_oldest_value(input);
+            let new_sum = prev_sum.saturating_sub(remove).saturating_add(new_value);
+            self.sum = Some(new_sum);
+            Self::saturate(self.scaled_average(new_sum))
+        };
+        self.track_change(average);
+        average
     }
 
-    #[expect(clippy::expect_used, reason = "Made safe by compile-time asserts")]
-    fn create_average(&mut self, new_value: TCALC, prev_sum: TCALC, remove: TCALC) -> T {
-        let new_sum = prev_sum + new_value - remove;
-        self.sum = Some(new_sum);
-        let average_as_tcalc = new_sum / self.num;
-        T::try_from(average_as_tcalc).expect("Converting from TCALC to T should be safe")
+    /// # Intent
+    /// Averages `input` like `average`, but until the window holds only real
+    /// samples returns the mean of the real samples only, without the bias
+    /// of the PREFILL copies of the first sample. Once the window is full
+    /// of real samples, this is the same as `average`.
+    ///
+    /// The fill copies are all the oldest value, so their share of the
+    /// sum is removed in O(1). Without PREFILL, `average` already divides
+    /// by the samples seen.
+    #[must_use]
+    pub fn average_partial(&mut self, input: T) -> T {
+        let average = self.average(input);
+        let missing = self.active - self.len();
+        if !PREFILL || missing == 0 || self.is_empty() || self.ramp.is_some() {
+            return average;
+        }
+        let (Some(sum), Some(&oldest)) = (self.sum, self.window().next()) else {
+            return average;
+        };
+        let (Ok(copies), Ok(observed)) = (TCALC::try_from(missing), TCALC::try_from(self.len()))
+        else {
+            return average;
+        };
+        let (numerator, denominator) =
+            self.scaled_fraction_over(sum - TCALC::from(oldest) * copies, observed);
+        Self::narrow(numerator / denominator)
     }
 
-    #[expect(clippy::expect_used, reason = "Made safe by compile-time asserts")]
-    fn get_remove_value(&self) -> TCALC {
-        #[cfg(test)]
-        assert!(
-            self.buffer.len() == N,
-            "Buffer len {} different than capacity {N}.",
-            self.buffer.len()
-        );
+    /// # Intent
+    /// Averages `input` like `average`, but returns the average rounded to
+    /// the nearest integer instead of truncated towards zero, with halves
+    /// rounded away from zero. E.g. `[3, 4]` gives 4, and `[-3, -4]` gives
+    /// -4.
+    ///
+    /// The rounding never overflows TCALC, so needs no extra headroom. It
+    /// applies to the scaled average, and a `transition_to` ramp in
+    /// progress takes precedence, as for `average`.
+    #[must_use]
+    pub fn average_rounded(&mut self, input: T) -> T {
+        self.average_by(input, Rounding::Nearest)
+    }
 
-        TCALC::from(
-            *self
-                .buffer
-                .oldest_ordered()
-                .next()
-                .expect("Buffer should be full"),
-        )
+    /// # Intent
+    /// Averages `input` like `average`, but returns the average rounded
+    /// towards negative infinity instead of towards zero, so averages of
+    /// mixed-sign data aren't biased upwards. E.g. `[-1, -2]` gives -2,
+    /// where `average` gives -1. With an unsigned TCALC this is `average`.
+    ///
+    /// It applies to the scaled average, and a `transition_to` ramp in
+    /// progress takes precedence, as for `average`.
+    #[must_use]
+    pub fn average_floored(&mut self, input: T) -> T {
+        self.average_by(input, Rounding::Floor)
     }
-}
 
-#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
-#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
-#[expect(clippy::cast_possible_truncation, reason = "Desirable in tests")]
-#[expect(clippy::cast_possible_wrap, reason = "Desirable in tests")]
-#[cfg(test)]
-mod tests {
-    use super::MovingAverage;
+    /// # Intent
+    /// Averages each sample of `samples` in turn, as repeated `average`
+    /// calls would, and returns the final average, e.g. for a burst of ADC
+    /// readings.
+    ///
+    /// Returns the current average if `samples` is empty, or `None` if it
+    /// is empty and no samples have been recorded.
+    #[must_use]
+    pub fn extend<I: IntoIterator<Item = T>>(&mut self, samples: I) -> Option<T> {
+        samples
+            .into_iter()
+            .fold(self.current_average(), |_, value| Some(self.average(value)))
+    }
 
-    #[test]
-    fn given_new_moving_average_when_average_value_then_return_same_value() {
-        let mut sut = MovingAverage::<u32, u64, 1>::new();
-        let expected: u32 = 44;
-        assert_eq!(expected, sut.average(expected));
+    /// # Intent
+    /// Averages `input` and passes the result through `map`, e.g. to turn
+    /// smoothed ADC counts into an engineering unit of another type.
+    #[must_use]
+    pub fn average_mapped<U, F: Fn(T) -> U>(&mut self, input: T, map: F) -> U {
+        map(self.average(input))
     }
 
-    #[test]
-    fn given_two_item_moving_average_when_average_twice_value_then_return_average_of_those_values()
+    /// # Intent
+    /// Averages `input`, returning the average along with its `Quality`,
+    /// so callers can gate on one typed status. `Saturated` takes
+    /// precedence over `Warming`.
+    #[must_use]
+    pub fn average_status(&mut self, input: T) -> (T, Quality) {
+        let average = self.average(input);
+        let saturated = self
+            .sum
+            .is_some_and(|sum| T::try_from(self.scaled_average(sum)).is_err());
+        let quality = if saturated {
+            Quality::Saturated
+        } else if !self.is_full() {
+            Quality::Warming
+        } else {
+            Quality::Valid
+        };
+        (average, quality)
+    }
+
+    /// # Intent
+    /// Averages `input` `count` times, as a loop of `average` calls would.
+    /// Once N copies have been averaged, including behind a shorter active
+    /// window, and no ramp is in progress, every further call returns the
+    /// same average, so the rest are only counted. This makes large counts
+    /// O(N) rather than O(`count`).
+    ///
+    /// A `count` of zero returns the current average, or `input` if none.
+    #[must_use]
+    pub fn average_repeated(&mut self, input: T, count: usize) -> T {
+        let mut average = self.current_average().unwrap_or(input);
+        let mut averaged = 0;
+        while averaged < count && (averaged < N || self.ramp.is_some()) {
+            average = self.average(input);
+            averaged += 1;
+        }
+        let skipped = u32::try_from(count - averaged).unwrap_or(u32::MAX);
+        self.samples_since_change = self.samples_since_change.saturating_add(skipped);
+        average
+    }
+
+    /// # Intent
+    /// Unpacks `bytes` as a little-endian bit stream of unsigned samples
+    /// `bits_per_sample` wide, feeds each through `average`, and returns the
+    /// final average. Bits left over after the last complete sample are
+    /// ignored.
+    ///
+    /// Returns `None`, feeding nothing, if `bits_per_sample` isn't one of
+    /// 8, 10, 12 or 16, or if the widest sample wouldn't fit in T. Also
+    /// returns `None` if no sample has ever been averaged.
+    #[must_use]
+    pub fn average_packed(&mut self, bytes: &[u8], bits_per_sample: u8) -> Option<T>
+    where
+        T: TryFrom<u16>,
     {
-        let mut sut = MovingAverage::<u32, u64, 2>::new();
-        let first: u32 = 22;
-        let second: u32 = 44;
-        let expected = (first + second) / 2;
-        let _ = sut.average(first);
-        assert_eq!(expected, sut.average(second));
+        if ![8_u8, 10_u8, 12_u8, 16_u8].contains(&bits_per_sample) {
+            return None;
+        }
+        let width = u32::from(bits_per_sample);
+        let mask = (1_u32 << width) - 1;
+        T::try_from(u16::try_from(mask).ok()?).ok()?;
+        let (mut acc, mut acc_bits) = (0_u32, 0_u32);
+        let mut average = self.current_average();
+        for &byte in bytes {
+            acc |= u32::from(byte) << acc_bits;
+            acc_bits += 8;
+            while acc_bits >= width {
+                let raw = u16::try_from(acc & mask).ok()?;
+                average = Some(self.average(T::try_from(raw).ok()?));
+                acc >>= width;
+                acc_bits -= width;
+            }
+        }
+        average
     }
 
-    #[test]
-    fn given_two_item_moving_average_when_average_called_thrice_then_return_average_of_the_last_two_values()
-     {
-        let mut sut = MovingAverage::<u32, u64, 2>::new();
-        let first: u32 = 22;
-        let second: u32 = 44;
-        let third: u32 = 66;
-        let expected = (second + third) / 2;
-        let _ = sut.average(first);
-        let _ = sut.average(second);
-        assert_eq!(expected, sut.average(third));
+    /// # Intent
+    /// Sets the smoothing factor, `alpha_num / alpha_den`, of the one-pole
+    /// IIR filter that `average_then_iir` cascades after the average. It
+    /// is checked once here rather than on every sample. The IIR state is
+    /// kept, so the factor can be changed on the fly.
+    ///
+    /// # Panics
+    /// Panics as `Ewma::new`, if `alpha_num / alpha_den` is zero or above
+    /// one, or `(T::MAX - T::MIN) * alpha_num` won't fit in TCALC.
+    /// With the `defmt-checks` feature, logs the error and leaves the
+    /// factor unchanged instead.
+    pub fn enable_iir(&mut self, alpha_num: TCALC, alpha_den: TCALC) {
+        finish_check(self.set_iir_alpha(alpha_num, alpha_den), || ());
     }
 
-    #[test]
-    fn given_two_signed_item_moving_average_when_average_called_thrice_then_return_average_of_the_last_two_values()
-     {
-        let mut sut = MovingAverage::<i32, i64, 2>::new();
-        let first: i32 = -22;
-        let second: i32 = 44;
-        let third: i32 = -66;
-        let expected = (second + third) / 2_i32;
-        let _ = sut.average(first);
-        let _ = sut.average(second);
-        assert_eq!(expected, sut.average(third));
+    /// # Intent
+    /// Sets the IIR smoothing factor as `enable_iir` does, for callers that
+    /// handle a bad factor themselves.
+    ///
+    /// # Errors
+    /// Returns the errors of `Ewma::try_new`, leaving the factor unchanged.
+    #[cfg(feature = "result-checks")]
+    pub fn try_enable_iir(
+        &mut self,
+        alpha_num: TCALC,
+        alpha_den: TCALC,
+    ) -> Result<(), MovingAverageError> {
+        self.set_iir_alpha(alpha_num, alpha_den)
     }
 
-    #[test]
-    fn given_large_item_moving_average_when_average_called_thrice_then_return_average_of_the_last_two_values()
-     {
-        const DEPTH: usize = 128;
-        let mut sut = MovingAverage::<i32, i64, DEPTH>::new();
-        let first: i32 = -22;
-        let second: i32 = 44;
-        let third: i32 = -66;
-        let expected = (first + second + third + (((DEPTH as i32) - 3_i32) * first)) / DEPTH as i32;
-        let _ = sut.average(first);
-        let _ = sut.average(second);
-        assert_eq!(expected, sut.average(third));
+    /// # Intent
+    /// Averages `input`, then smooths the average further with a one-pole
+    /// IIR filter, `state += (average - state) * alpha_num / alpha_den`,
+    /// for extra smoothing without a deeper window. The factor is set with
+    /// `enable_iir`, and until it is this returns the average unchanged.
+    /// The state starts at the first average.
+    ///
+    /// The step is truncated towards zero, so the state can settle short of
+    /// a constant average by up to `alpha_den / alpha_num`.
+    #[must_use]
+    pub fn average_then_iir(&mut self, input: T) -> T {
+        let average = self.average(input);
+        let Some((alpha_num, alpha_den)) = self.iir_alpha else {
+            return average;
+        };
+        let average_calc = TCALC::from(average);
+        let state = self.iir_state.map_or(average_calc, |state| {
+            step_toward(state, average_calc, alpha_num, alpha_den)
+        });
+        self.iir_state = Some(state);
+        Self::narrow(state)
     }
 
-    // Issue 1 replication case
-    // https://github.com/Radiator-Labs/no-std-moving-average-rs/issues/1
-    #[test]
-    fn given_enough_items_to_roll_over_when_average_called_then_return_average_of_the_correct_set_of_values()
-     {
-        let mut sut = MovingAverage::<u16, u32, 4>::new();
-        let sequence = [
+    /// # Intent
+    /// Ramps the output of `average` linearly from the current average to
+    /// `target` over the next `steps` calls, regardless of their inputs,
+    /// then resumes normal operation, e.g. when a setpoint changes. The
+    /// inputs are still averaged into the window during the ramp.
+    ///
+    /// The ramp starts from `target` if no samples have been recorded, and
+    /// a `steps` of zero cancels any ramp in progress.
+    /// `(T::MAX - T::MIN) * steps` must fit in TCALC.
+    ///
+    /// # Panics
+    /// Panics if `steps` won't fit in TCALC.
+    /// With the `defmt-checks` feature, logs the error and leaves any ramp
+    /// in progress unchanged instead.
+    pub fn transition_to(&mut self, target: T, steps: usize) {
+        finish_check(self.start_ramp(target, steps), || ());
+    }
+
+    /// # Intent
+    /// Ramps the output toward `target` as `transition_to` does, for
+    /// callers that handle a bad `steps` themselves.
+    ///
+    /// # Errors
+    /// Returns `CountOverflow` if `steps` won't fit in TCALC, leaving any
+    /// ramp in progress unchanged.
+    #[cfg(feature = "result-checks")]
+    pub fn try_transition_to(&mut self, target: T, steps: usize) -> Result<(), MovingAverageError> {
+        self.start_ramp(target, steps)
+    }
+
+    /// # Intent
+    /// Sets what `tick_missing` inserts for a missing sample. The default
+    /// is `MissingPolicy::Hold`.
+    pub fn set_missing_policy(&mut self, policy: MissingPolicy) {
+        self.missing_policy = policy;
+    }
+
+    /// # Intent
+    /// Averages a stand-in for a sample that didn't arrive this tick, as
+    /// chosen by `set_missing_policy`, so the window keeps advancing in
+    /// step with real time across gaps.
+    ///
+    /// Returns `None`, inserting nothing, if no samples have been recorded.
+    #[must_use]
+    pub fn tick_missing(&mut self) -> Option<T> {
+        let newest = *self.buffer.recent()?;
+        let stand_in = match self.missing_policy {
+            MissingPolicy::Hold => newest,
+            MissingPolicy::Extrapolate => self.extrapolate(newest),
+        };
+        Some(self.average(stand_in))
+    }
+
+    /// # Intent
+    /// Discards the history and stuffs the entire window with `value`, as
+    /// if it were the first sample.
+    pub fn fill(&mut self, value: T) {
+        for _ in 0..N {
+            self.buffer.write(value);
+        }
+        self.sum = Some(self.fill_sum(value));
+        self.observed = 0;
+    }
+
+    /// # Intent
+    /// Returns the filter to the state it had when constructed, e.g. to
+    /// reuse it across measurement sessions. The next `average` behaves as
+    /// the first call on a new filter, including the PREFILL warm-up fill.
+    /// The sentinel, output scale, missing-sample policy, active window and
+    /// IIR factor are kept.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.sum = None;
+        self.samples_since_change = 0;
+        self.last_output = None;
+        self.iir_state = None;
+        self.observed = 0;
+        self.ramp = None;
+    }
+
+    /// # Intent
+    /// Converts the filter into a `WarmedAverage` once the window holds
+    /// only real samples, so the warm-up contract is enforced by the type.
+    /// Samples from a fill, whether explicit or from PREFILL, don't count.
+    ///
+    /// Returns `None` if the window isn't full of real samples. The filter
+    /// is consumed either way.
+    #[must_use]
+    pub fn into_warmed(self) -> Option<WarmedAverage<T, TCALC, N, PREFILL, SATURATING>> {
+        self.is_full().then_some(WarmedAverage(self))
+    }
+
+    /// # Intent
+    /// Removes the oldest occurrence of `value` from the window, to retract
+    /// a sample found to be wrong, and returns the new average.
+    ///
+    /// The newer samples shift one place towards the oldest end. With
+    /// PREFILL the window must stay full, so the oldest remaining sample is
+    /// duplicated at the oldest end, as the warm-up fill would. Without
+    /// PREFILL the window shrinks by one. Retracting the only sample leaves
+    /// the filter as new, and returns `value`. Behind a shorter active
+    /// window, the stored sample next in age slides into it instead.
+    ///
+    /// The window is rebuilt and re-summed, so this costs O(N).
+    ///
+    /// # Errors
+    /// Returns `NotInWindow` if no sample in the window equals `value`.
+    pub fn retract(&mut self, value: T) -> Result<T, MovingAverageError> {
+        if self.sum.is_none() {
+            return Err(MovingAverageError::NotInWindow);
+        }
+        let position = self
+            .window()
+            .position(|&sample| sample == value)
+            .ok_or(MovingAverageError::NotInWindow)?
+            + self.buffer.len()
+            - self.window_len();
+        let (mut window, full_len) = self.stored_window();
+        let len = full_len - 1;
+        window.copy_within(position + 1..=len, position);
+        self.buffer.clear();
+        self.observed = self.observed.saturating_sub(1);
+        if len == 0 {
+            self.sum = None;
+            return Ok(value);
+        }
+        if PREFILL {
+            self.buffer.write(window[0]);
+        }
+        for &sample in &window[..len] {
+            self.buffer.write(sample);
+        }
+        let sum = self.window_sum();
+        self.sum = Some(sum);
+        Ok(self.narrow_average(sum))
+    }
+
+    /// # Intent
+    /// Takes the samples out of the window, oldest to newest, and leaves the
+    /// filter as new, e.g. at the end of a measurement window. As `reset`,
+    /// the configuration, including the active window, is kept.
+    ///
+    /// The filter is reset before the samples are returned, so it is left
+    /// as new however much of the `Drain` is read. Copies from a fill are
+    /// included, as for `iter`.
+    pub fn drain(&mut self) -> Drain<T, N> {
+        let (window, len) = self.ordered_window();
+        self.reset();
+        Drain {
+            window,
+            next: 0,
+            len,
+        }
+    }
+
+    /// # Intent
+    /// Overwrites the newest sample with `value`, to correct a known glitch
+    /// without disturbing the rest of the window, and returns the new
+    /// average.
+    ///
+    /// `HistoryBuffer` can't overwrite in place, so the window is rebuilt
+    /// with the older samples unchanged, which costs O(N).
+    ///
+    /// # Errors
+    /// Returns `EmptyWindow` if no samples have been recorded.
+    pub fn replace_newest(&mut self, value: T) -> Result<T, MovingAverageError> {
+        let prev_sum = self.sum.ok_or(MovingAverageError::EmptyWindow)?;
+        let (mut window, len) = self.stored_window();
+        let newest = window[len - 1];
+        window[len - 1] = value;
+        self.buffer.clear();
+        for &sample in &window[..len] {
+            self.buffer.write(sample);
+        }
+        let sum = prev_sum - TCALC::from(newest) + TCALC::from(value);
+        self.sum = Some(sum);
+        Ok(self.narrow_average(sum))
+    }
+
+    /// # Intent
+    /// Resets the window to be full of the current average, so a change of
+    /// configuration or mode doesn't make the output jump.
+    ///
+    /// Does nothing if no samples have been recorded.
+    pub fn reseed_preserving_average(&mut self) {
+        if let Some(sum) = self.sum {
+            self.fill(Self::narrow(sum / self.divisor()));
+        }
+    }
+
+    /// # Intent
+    /// Averages `input` normally, unless it is more than `jump_threshold`
+    /// away from the current average. Then the window is `fill`ed with
+    /// `input`, so the average re-converges instantly after a step instead
+    /// of dragging through the old history.
+    #[must_use]
+    pub fn average_reset_on_jump(&mut self, input: T, jump_threshold: T) -> T {
+        let jumped = self.sentinel != Some(input)
+            && self.current_average().is_some_and(|average| {
+                abs_diff(TCALC::from(input), TCALC::from(average)) > TCALC::from(jump_threshold)
+            });
+        if jumped {
+            self.fill(input);
+            self.current_average().unwrap_or(input)
+        } else {
+            self.average(input)
+        }
+    }
+
+    /// # Intent
+    /// Returns how many consecutive `average` calls have returned the same
+    /// value as the call before, saturating at `u32::MAX`. A stuck sensor
+    /// shows up as a steadily growing count.
+    #[must_use]
+    pub fn samples_since_change(&self) -> u32 {
+        self.samples_since_change
+    }
+
+    /// # Intent
+    /// Returns the average of the current window without inserting a
+    /// sample, or `None` if no samples have been recorded.
+    #[must_use]
+    pub fn current_average(&self) -> Option<T> {
+        self.sum.map(|sum| self.narrow_average(sum))
+    }
+
+    /// # Intent
+    /// Returns the running sum of the window, for statistics built on top
+    /// of the filter, or `None` if no samples have been recorded. With
+    /// PREFILL the sum includes the fill.
+    #[must_use]
+    pub fn current_sum(&self) -> Option<TCALC> {
+        self.sum
+    }
+
+    /// # Intent
+    /// Iterates over the window, oldest sample first, e.g. for custom
+    /// statistics. Copies from a fill, explicit or from PREFILL, are
+    /// included, as they take part in the average.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.window()
+    }
+
+    /// # Intent
+    /// Reduces the window, oldest sample first, with `f`, starting from
+    /// `init`, for custom aggregates such as RMS without allocating. The
+    /// window is the same as for `iter`, and `average` is untouched.
+    ///
+    /// ```rust
+    /// use no_std_moving_average::MovingAverage;
+    ///
+    /// let mut filter = MovingAverage::<u16, u32, 4, false>::new();
+    /// for sample in [1_u16, 2_u16, 3_u16] {
+    ///     let _ = filter.average(sample);
+    /// }
+    /// let squares = filter.fold(0_u32, |total, &sample| {
+    ///     total + u32::from(sample) * u32::from(sample)
+    /// });
+    /// assert_eq!(14_u32, squares);
+    /// ```
+    pub fn fold<B, F: Fn(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.window().fold(init, f)
+    }
+
+    /// # Intent
+    /// Returns the oldest real sample in the window without removing it,
+    /// e.g. for a rate of change, or `None` if no real samples have been
+    /// recorded. This is the chronologically oldest sample, not the first
+    /// slot of the ring, and copies from a fill are skipped.
+    #[must_use]
+    pub fn oldest(&self) -> Option<T> {
+        self.real_samples().next().copied()
+    }
+
+    /// # Intent
+    /// Returns the most recent sample without removing it, or `None` if no
+    /// real samples have been recorded.
+    #[must_use]
+    pub fn newest(&self) -> Option<T> {
+        if self.observed == 0 {
+            return None;
+        }
+        self.buffer.recent().copied()
+    }
+
+    /// # Intent
+    /// Returns the underlying window, for `HistoryBuffer` methods this
+    /// crate doesn't wrap, such as `recent()`. Behind a shorter active
+    /// window it also holds the older samples kept in storage.
+    ///
+    /// Only immutable access is offered, as writing to the buffer directly
+    /// would break the running sum.
+    #[must_use]
+    pub fn buffer(&self) -> &HistoryBuffer<T, N> {
+        &self.buffer
+    }
+
+    /// # Intent
+    /// Returns the number of real samples in the window, up to the active
+    /// window. Copies from a fill, whether explicit or from PREFILL, don't
+    /// count, so with PREFILL this can be below `buffer().len()`.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.observed.min(self.active)
+    }
+
+    /// # Intent
+    /// Returns true if no real samples are in the window.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.observed == 0
+    }
+
+    /// # Intent
+    /// Returns true once the window holds only real samples, N of them
+    /// unless the active window is shorter, so acting on the average can
+    /// wait until it no longer depends on the warm-up.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.observed >= self.active
+    }
+
+    /// # Intent
+    /// Returns true once the window holds only real samples, so a control
+    /// loop can ignore the output until then. While PREFILL copies fill the
+    /// window it returns false, although the buffer is physically full.
+    /// This is `is_full`, named for the readiness gate.
+    #[must_use]
+    pub fn is_warmed(&self) -> bool {
+        self.is_full()
+    }
+
+    /// # Intent
+    /// Returns the depth of the window, N, for generic code where N isn't
+    /// nameable. It is a `const fn`, so can size arrays in const contexts.
+    ///
+    /// ```rust
+    /// use no_std_moving_average::MovingAverage;
+    ///
+    /// let filter = MovingAverage::<u16, u32, 8>::new();
+    /// assert_eq!(8, filter.capacity());
+    /// ```
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// # Intent
+    /// Returns the number of samples averaged over, N unless shortened by
+    /// `set_active_window`.
+    #[must_use]
+    pub const fn active_window(&self) -> usize {
+        self.active
+    }
+
+    /// # Intent
+    /// Averages over only the newest `window` samples, from 1 up to N,
+    /// without a new filter. The `[T; N]` storage is kept, and samples
+    /// behind the active window are still stored, so lengthening it again
+    /// brings them back into the average.
+    ///
+    /// The running sum is recomputed over the new window, which costs
+    /// O(`window`). Shrinking therefore costs as much as the window kept,
+    /// but `average` stays O(1) at any length.
+    ///
+    /// # Panics
+    /// Panics if `window` is not in `1..=N`.
+    /// Panics if `window` won't fit in TCALC, prevented by the capacity
+    /// check at construction.
+    /// With the `defmt-checks` feature, logs the error and leaves the
+    /// window unchanged instead.
+    pub fn set_active_window(&mut self, window: usize) {
+        finish_check(self.resize_active_window(window), || ());
+    }
+
+    /// # Intent
+    /// Changes the active window as `set_active_window` does, for callers
+    /// that handle a bad length themselves.
+    ///
+    /// # Errors
+    /// Returns `InvalidLength` if `window` is not in `1..=N`, leaving the
+    /// window unchanged.
+    #[cfg(feature = "result-checks")]
+    pub fn try_set_active_window(&mut self, window: usize) -> Result<(), MovingAverageError> {
+        self.resize_active_window(window)
+    }
+
+    /// # Intent
+    /// Packs the window into `out` as a byte frame, oldest to newest, with
+    /// each sample in little-endian order, ready to send over DMA or UART.
+    ///
+    /// Returns the number of bytes written, which is
+    /// `active_window() * size_of::<T>()` once the window is full. Nothing
+    /// is written, and 0 is returned, if `out` is shorter than that.
+    #[must_use]
+    pub fn pack_window(&self, out: &mut [u8]) -> usize {
+        let size = size_of::<T>();
+        if out.len() < self.active * size {
+            return 0;
+        }
+        for (bytes, &value) in out.chunks_exact_mut(size).zip(self.window()) {
+            value.write_le_bytes(bytes);
+        }
+        self.window_len() * size
+    }
+
+    /// # Intent
+    /// Copies the window as it was before inserting `input` into `out`,
+    /// oldest to newest, then returns the average after the insertion.
+    ///
+    /// Before the first sample the window is reported as the warm-up fill
+    /// of `input`, matching what `average` stuffs into the buffer. Without
+    /// PREFILL, only the samples seen so far are copied to the front of
+    /// `out`. Behind a shorter active window, only its samples are copied.
+    #[must_use]
+    pub fn average_and_window(&mut self, input: T, out: &mut [T; N]) -> T {
+        if PREFILL && self.sum.is_none() {
+            out[..self.active].fill(input);
+        } else {
+            for (slot, value) in out.iter_mut().zip(self.window()) {
+                *slot = *value;
+            }
+        }
+        self.average(input)
+    }
+
+    /// # Intent
+    /// Feeds `input` through `average` and returns the new mean together
+    /// with the minimum and maximum of the window, found in a single scan.
+    /// Only the mean is scaled or ramped, the minimum and maximum are
+    /// samples from the window.
+    #[must_use]
+    pub fn summary(&mut self, input: T) -> Summary<T>
+    where
+        T: PartialOrd,
+    {
+        let mean = self.average(input);
+        let first = self.window().next().copied().unwrap_or(input);
+        let (min, max) = self.window().fold((first, first), |(min, max), &value| {
+            (
+                if value < min { value } else { min },
+                if value > max { value } else { max },
+            )
+        });
+        Summary { mean, min, max }
+    }
+
+    /// # Intent
+    /// Returns the average, sum, sample count, minimum, maximum and
+    /// variance together, found in a single scan of the window, so a
+    /// logging hook needs one call per cycle.
+    ///
+    /// The variance requires `N * T::MAX^2` to fit in TCALC.
+    ///
+    /// Returns `None` if no samples have been recorded.
+    #[must_use]
+    pub fn diagnostics(&self) -> Option<Diagnostics<T, TCALC>>
+    where
+        T: PartialOrd,
+    {
+        let sum = self.sum?;
+        let average = self.current_average()?;
+        let mean = sum / self.divisor();
+        let first = *self.window().next()?;
+        let (min, max, squares) = self.window().fold(
+            (first, first, TCALC::ZERO),
+            |(min, max, squares), &value| {
+                let deviation = abs_diff(TCALC::from(value), mean);
+                (
+                    if value < min { value } else { min },
+                    if value > max { value } else { max },
+                    squares + deviation * deviation,
+                )
+            },
+        );
+        Some(Diagnostics {
+            average,
+            sum,
+            count: self.window_len(),
+            min,
+            max,
+            variance: squares / self.divisor(),
+        })
+    }
+
+    /// # Intent
+    /// Feeds `input` through `average`, then returns the average when the
+    /// window is quiet, or the raw `input` when the window variance exceeds
+    /// `var_threshold`, trading smoothing for lag during fast transients.
+    ///
+    /// The variance is the integer population variance of the window, and
+    /// requires `N * T::MAX²` to fit in TCALC.
+    #[must_use]
+    pub fn adaptive_average(&mut self, input: T, var_threshold: TCALC) -> T {
+        let average = self.average(input);
+        if self.sentinel == Some(input) {
+            return average;
+        }
+        match self.variance() {
+            Some(variance) if variance > var_threshold => input,
+            Some(_) | None => average,
+        }
+    }
+
+    /// # Intent
+    /// Returns the mean absolute difference between consecutive samples in
+    /// the window, a cheap jitter metric.
+    ///
+    /// The differences are summed in TCALC, which needs
+    /// `(N - 1) * (T::MAX - T::MIN)` to fit. For a signed T that is about
+    /// twice the headroom the capacity check guarantees.
+    ///
+    /// Returns `None` if the window holds less than 2 samples.
+    #[must_use]
+    pub fn mean_consecutive_diff(&self) -> Option<TCALC> {
+        let len = self.window_len();
+        if len < 2 {
+            return None;
+        }
+        let mut values = self.window().map(|&value| TCALC::from(value));
+        let first = values.next()?;
+        let (total, _) = values.fold((TCALC::ZERO, first), |(total, previous), value| {
+            (total + abs_diff(value, previous), value)
+        });
+        Some(total / TCALC::try_from(len - 1).ok()?)
+    }
+
+    /// # Intent
+    /// Returns the dot product of the window, oldest to newest, with
+    /// `coeffs`, turning the window into the tap line of a FIR filter or
+    /// matched filter.
+    ///
+    /// The products are summed in TCALC, which needs
+    /// `N * T::MAX * max(|coeffs|)` to fit. The capacity check only covers
+    /// coefficients of one. Behind a shorter active window, only the first
+    /// `active_window()` coefficients are used.
+    ///
+    /// Returns `None` until the window is full.
+    #[must_use]
+    pub fn correlate(&self, coeffs: &[TCALC; N]) -> Option<TCALC> {
+        if self.window_len() < self.active {
+            return None;
+        }
+        Some(
+            self.window()
+                .zip(coeffs)
+                .fold(TCALC::ZERO, |total, (&value, &coeff)| {
+                    total + TCALC::from(value) * coeff
+                }),
+        )
+    }
+
+    /// # Intent
+    /// Returns the average of the window weighted by inverse recency, the
+    /// oldest sample having weight `len` and the newest weight 1, divided by
+    /// the triangular number `len * (len + 1) / 2`. This deliberately lags
+    /// more than `average`, for a slow reference signal.
+    ///
+    /// The products are summed in TCALC, which needs
+    /// `N * (N + 1) / 2 * T::MAX` to fit. The capacity check only covers N.
+    ///
+    /// Returns `None` if no samples have been recorded.
+    #[must_use]
+    pub fn average_anti_recency(&self) -> Option<T> {
+        let len = self.window_len();
+        if len == 0 {
+            return None;
+        }
+        let divisor = TCALC::try_from(len * (len + 1) / 2).ok()?;
+        let (total, _) = self.window().fold(
+            (TCALC::ZERO, TCALC::try_from(len).ok()?),
+            |(total, weight), &value| (total + TCALC::from(value) * weight, weight - TCALC::ONE),
+        );
+        T::try_from(total / divisor).ok()
+    }
+
+    /// # Intent
+    /// Returns the most common value in the window and how many samples
+    /// hold it, as a quick check for a pinned signal. When several values
+    /// are equally common, the smallest is returned.
+    ///
+    /// The window is copied and sorted, so this costs O(N²) per call.
+    ///
+    /// Returns `(None, 0)` if no samples have been recorded.
+    #[must_use]
+    pub fn mode_count(&self) -> (Option<T>, usize)
+    where
+        T: PartialOrd,
+    {
+        let (window, len) = self.sorted_window();
+        let mut mode = (None, 0);
+        let mut run = 0;
+        for (i, &value) in window[..len].iter().enumerate() {
+            run = if i > 0 && window[i - 1] == value {
+                run + 1
+            } else {
+                1
+            };
+            if run > mode.1 {
+                mode = (Some(value), run);
+            }
+        }
+        mode
+    }
+
+    /// # Intent
+    /// Returns true if the window is split into two levels, as from a
+    /// sensor stuck toggling. That is when exactly one step between sorted
+    /// neighbours is larger than `gap`, and each side of it holds at least a
+    /// quarter of the window, and at least one sample.
+    ///
+    /// The window is copied and sorted, so this costs O(N²) per call.
+    #[must_use]
+    pub fn is_bimodal(&self, gap: T) -> bool
+    where
+        T: PartialOrd,
+    {
+        let (window, len) = self.sorted_window();
+        let max_step = TCALC::from(gap);
+        let mut split = None;
+        for i in 1..len {
+            if TCALC::from(window[i]) - TCALC::from(window[i - 1]) > max_step {
+                if split.is_some() {
+                    return false;
+                }
+                split = Some(i);
+            }
+        }
+        let min_cluster = (len / 4).max(1);
+        split.is_some_and(|i| i >= min_cluster && len - i >= min_cluster)
+    }
+
+    /// # Intent
+    /// Returns the mean of the `k` largest samples in the window, or of the
+    /// whole window if it holds fewer, e.g. for peak power estimation.
+    ///
+    /// The window is copied and sorted, so this costs O(N²) per call.
+    ///
+    /// Returns `None` if no samples have been recorded or `k` is zero.
+    #[must_use]
+    pub fn top_k_average(&self, k: usize) -> Option<T>
+    where
+        T: PartialOrd,
+    {
+        let (window, len) = self.sorted_window();
+        let count = k.min(len);
+        if count == 0 {
+            return None;
+        }
+        let total = window[len - count..len]
+            .iter()
+            .fold(TCALC::ZERO, |total, &value| total + TCALC::from(value));
+        Some(Self::narrow(total / TCALC::try_from(count).ok()?))
+    }
+
+    /// # Intent
+    /// Returns the average of `len` samples starting `offset` samples after
+    /// the oldest, treating the window as circular so that a view running
+    /// past the newest sample wraps around to the oldest.
+    ///
+    /// Returns `None` if the window is empty, if `len` is zero or larger
+    /// than the window, or if `offset` is not inside the window.
+    #[must_use]
+    pub fn average_from_offset(&self, offset: usize, len: usize) -> Option<T> {
+        let count = self.window_len();
+        if len == 0 || len > count || offset >= count {
+            return None;
+        }
+        let total = self
+            .window()
+            .chain(self.window())
+            .skip(offset)
+            .take(len)
+            .fold(TCALC::ZERO, |total, &value| total + TCALC::from(value));
+        Some(Self::narrow(total / TCALC::try_from(len).ok()?))
+    }
+
+    /// # Intent
+    /// Returns the average of the window samples at positions `start`,
+    /// `start + step`, `start + 2 * step` and so on, counted from the
+    /// oldest, for polyphase access to a decimated window.
+    ///
+    /// Returns `None` until the window is full, or if `step` is zero or
+    /// `start` is not inside the window, selecting nothing.
+    #[must_use]
+    pub fn average_strided_window(&self, start: usize, step: usize) -> Option<T> {
+        if self.window_len() < self.active || step == 0 || start >= self.active {
+            return None;
+        }
+        let (total, count) = self
+            .window()
+            .skip(start)
+            .step_by(step)
+            .fold((TCALC::ZERO, 0_usize), |(total, count), &value| {
+                (total + TCALC::from(value), count + 1)
+            });
+        Some(Self::narrow(total / TCALC::try_from(count).ok()?))
+    }
+
+    /// # Intent
+    /// Returns the smallest real sample in the window, e.g. for a rolling
+    /// minimum over the same window as the average. Copies from a fill,
+    /// explicit or from PREFILL, are left out, as for `len`.
+    ///
+    /// The window is scanned on each call, costing O(N).
+    ///
+    /// Returns `None` if the window holds no real samples.
+    #[must_use]
+    pub fn min(&self) -> Option<T>
+    where
+        T: PartialOrd,
+    {
+        self.real_samples()
+            .copied()
+            .reduce(|min, value| if value < min { value } else { min })
+    }
+
+    /// # Intent
+    /// Returns the largest real sample in the window, to catch spikes the
+    /// average smooths away. Copies from a fill are left out, as for `min`.
+    ///
+    /// The window is scanned on each call, costing O(N).
+    ///
+    /// Returns `None` if the window holds no real samples.
+    #[must_use]
+    pub fn max(&self) -> Option<T>
+    where
+        T: PartialOrd,
+    {
+        self.real_samples()
+            .copied()
+            .reduce(|max, value| if value > max { value } else { max })
+    }
+
+    /// # Intent
+    /// Returns `(min, max)` of the real samples in the window, found in a
+    /// single scan.
+    ///
+    /// Returns `None` if the window holds no real samples.
+    #[must_use]
+    pub fn min_max(&self) -> Option<(T, T)>
+    where
+        T: PartialOrd,
+    {
+        let mut samples = self.real_samples().copied();
+        let first = samples.next()?;
+        Some(samples.fold((first, first), |(min, max), value| {
+            (
+                if value < min { value } else { min },
+                if value > max { value } else { max },
+            )
+        }))
+    }
+
+    /// # Intent
+    /// Returns the minimum and maximum of the window along with how many
+    /// samples ago each was recorded, to anticipate when a spike will fall
+    /// out of the window. When an extreme occurs more than once, the newest
+    /// occurrence is reported, as the extreme stays until it is evicted.
+    ///
+    /// Returns `None` if no samples have been recorded.
+    #[must_use]
+    pub fn extremes_with_position(&self) -> Option<Extremes<T>>
+    where
+        T: PartialOrd,
+    {
+        let newest = *self.buffer.recent()?;
+        let start = Extremes {
+            min: newest,
+            min_age: 0,
+            max: newest,
+            max_age: 0,
+        };
+        let len = self.window_len();
+        Some(
+            self.window()
+                .enumerate()
+                .fold(start, |mut extremes, (i, &value)| {
+                    if value <= extremes.min {
+                        extremes.min = value;
+                        extremes.min_age = len - 1 - i;
+                    }
+                    if value >= extremes.max {
+                        extremes.max = value;
+                        extremes.max_age = len - 1 - i;
+                    }
+                    extremes
+                }),
+        )
+    }
+
+    /// # Intent
+    /// Returns how many `average` calls the oldest occurrence of `value`
+    /// survives, so 0 means the next call evicts it, for counting down the
+    /// influence of a marked sample. While a window without PREFILL is
+    /// still filling nothing is evicted, and the count includes the calls
+    /// left to fill it.
+    ///
+    /// Returns `None` if `value` is not in the window.
+    #[must_use]
+    pub fn samples_until_eviction(&self, value: T) -> Option<usize> {
+        let position = self.window().position(|&sample| sample == value)?;
+        Some(self.active - self.window_len() + position)
+    }
+
+    /// # Intent
+    /// Returns the integer population variance of the window, the mean of
+    /// the squared deviations from the average, e.g. for anomaly detection.
+    ///
+    /// The deviations are taken from the running average, truncated as
+    /// `average` does, rather than as the sum of squares minus the square
+    /// of the sum, which would need much more headroom. The truncation can
+    /// raise the result by under one. The sum of squares requires
+    /// `N * (T::MAX - T::MIN)^2` to fit in TCALC, which
+    /// `validate_variance_depth` checks.
+    ///
+    /// Returns `None` if no samples have been recorded.
+    #[must_use]
+    pub fn variance(&self) -> Option<TCALC> {
+        let mean = self.sum? / self.divisor();
+        let squares = self.window().fold(TCALC::ZERO, |squares, &value| {
+            let deviation = abs_diff(TCALC::from(value), mean);
+            squares + deviation * deviation
+        });
+        Some(squares / self.divisor())
+    }
+
+    /// # Intent
+    /// Returns the standard error of the mean, the window standard
+    /// deviation divided by the square root of the sample count, as a rough
+    /// ± figure around the average.
+    ///
+    /// The variance, both square roots, and the division all truncate, so
+    /// the result can be somewhat below the exact value. The variance
+    /// requires `N * T::MAX²` to fit in TCALC.
+    ///
+    /// Returns `None` if no samples have been recorded.
+    #[must_use]
+    pub fn standard_error(&self) -> Option<TCALC> {
+        Some(isqrt(self.variance()?) / isqrt(self.divisor()))
+    }
+
+    /// # Intent
+    /// The fixed scale of `zscore`, which reports thousandths of a standard
+    /// deviation so the fraction isn't lost to integer division.
+    pub const ZSCORE_SCALE: usize = 1000;
+
+    /// # Intent
+    /// Returns the z-score of `input` against the current window, without
+    /// inserting it, as `(input - mean) * ZSCORE_SCALE / std_dev`. A score
+    /// of 2000 is two standard deviations above the mean.
+    ///
+    /// The sign needs a signed TCALC. With an unsigned TCALC the magnitude
+    /// is returned. The variance requires `N * T::MAX^2` to fit in TCALC,
+    /// and `(T::MAX - T::MIN) * ZSCORE_SCALE` must fit too.
+    ///
+    /// Returns `None` until the window is full, or if the window is
+    /// constant, giving a standard deviation of zero.
+    #[must_use]
+    pub fn zscore(&self, input: T) -> Option<TCALC> {
+        if self.window_len() < self.active {
+            return None;
+        }
+        let std_dev = isqrt(self.variance()?);
+        if std_dev == TCALC::ZERO {
+            return None;
+        }
+        let mean = self.sum? / self.divisor();
+        let value = TCALC::from(input);
+        let scaled = abs_diff(value, mean) * TCALC::try_from(Self::ZSCORE_SCALE).ok()? / std_dev;
+        Some(if value < mean && TCALC::SIGNED {
+            TCALC::ZERO - scaled
+        } else {
+            scaled
+        })
+    }
+
+    /// # Intent
+    /// Returns the average of each contiguous block of B samples in the
+    /// window, oldest block first, for multi-resolution analysis from one
+    /// buffer.
+    ///
+    /// Stable Rust can't express the `[T; N / B]` return type, so the block
+    /// count M is a second parameter, and `M * B == N` is a compile-time
+    /// assert.
+    ///
+    /// Returns `None` until the window is full, or while the active window
+    /// is shorter than N.
+    ///
+    /// # Panics
+    /// Panics if B is zero or `M * B` isn't N, compile-time assert.
+    ///
+    /// ```compile_fail
+    /// use no_std_moving_average::MovingAverage;
+    /// let sut = MovingAverage::<u16, u32, 8>::new();
+    /// let _blocks = sut.block_averages::<3, 2>();
+    /// ```
+    #[must_use]
+    pub fn block_averages<const B: usize, const M: usize>(&self) -> Option<[T; M]> {
+        const {
+            assert!(B > 0 && M * B == N, "N must be M blocks of B samples");
+        }
+        if self.active < N || self.buffer.len() < N {
+            return None;
+        }
+        let block_len = TCALC::try_from(B).ok()?;
+        let mut averages = [T::ZERO; M];
+        let mut values = self.buffer.oldest_ordered();
+        for average in &mut averages {
+            let total = values
+                .by_ref()
+                .take(B)
+                .fold(TCALC::ZERO, |total, &value| total + TCALC::from(value));
+            *average = Self::narrow(total / block_len);
+        }
+        Some(averages)
+    }
+
+    /// # Intent
+    /// Returns true if `input` is more than `k` median absolute deviations
+    /// (MAD) away from the window median, without inserting it. The median
+    /// and MAD are robust to skewed noise and to the outliers themselves,
+    /// unlike the mean and standard deviation.
+    ///
+    /// Even-length medians are the lower middle value, as for `median`.
+    /// On a flat window the MAD is zero, so any different `input` is an
+    /// outlier. The window is copied and sorted twice, so this costs
+    /// O(N²) per call.
+    ///
+    /// Returns false if no samples have been recorded, or if `k * MAD`
+    /// overflows TCALC, as no deviation of a `T` can then exceed it.
+    #[must_use]
+    pub fn is_outlier(&self, input: T, k: TCALC) -> bool {
+        self.median_and_mad().is_some_and(|(median, mad)| {
+            k.checked_mul(mad)
+                .is_some_and(|limit| abs_diff(TCALC::from(input), median) > limit)
+        })
+    }
+
+    /// # Intent
+    /// Returns the rolling median of the window, robust against the
+    /// outliers that pull the mean, or `None` if no samples have been
+    /// recorded. For an even length this is the lower of the two middle
+    /// values, so it is always a sample from the window. Copies from a fill
+    /// are included, as they take part in the average.
+    ///
+    /// Unlike the O(1) `average`, the window is copied and insertion
+    /// sorted, without allocating, so this costs O(N^2) per call.
+    #[must_use]
+    pub fn median(&self) -> Option<T>
+    where
+        T: PartialOrd,
+    {
+        let (window, len) = self.sorted_window();
+        (len > 0).then(|| median_of_sorted(&window[..len]))
+    }
+
+    /// # Intent
+    /// Returns the window mean minus the window median, a quick skewness
+    /// figure. A large positive gap indicates a right-skewed window. The
+    /// median is the one `median` returns.
+    ///
+    /// The output scale is not applied. The window is copied and sorted, so
+    /// this costs O(N²) per call.
+    ///
+    /// Returns `None` if no samples have been recorded, or if the gap
+    /// cannot be represented in T (a negative gap with unsigned T).
+    #[must_use]
+    pub fn mean_median_gap(&self) -> Option<T> {
+        let mean = self.sum? / self.divisor();
+        let (values, len) = self.sorted_calc_window()?;
+        let median = median_of_sorted(&values[..len]);
+        if mean < median && !TCALC::SIGNED {
+            return None;
+        }
+        T::try_from(mean - median).ok()
+    }
+
+    /// # Intent
+    /// Returns the approximate -3 dB cutoff of the filter as a reduced
+    /// fraction of the sample rate, to help pick N for a desired cutoff.
+    ///
+    /// The approximation is `0.443 / N`, i.e. `443 / (1000 * N)`, which is
+    /// within 3% of the exact cutoff for N of 4 or more. For N of 2 the
+    /// exact cutoff is `1 / 4`, and a depth of 1 doesn't filter at all.
+    /// A shorter active window is used in place of N.
+    ///
+    /// # Panics
+    /// Panics if `1000 * N` won't fit in u32, compile-time assert.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Made safe by the compile-time assert"
+    )]
+    pub const fn cutoff_fraction(&self) -> (u32, u32) {
+        const {
+            assert!(N <= (u32::MAX / 1000) as usize, "1000 * N must fit in u32");
+        }
+        let den = 1000 * (self.active as u32);
+        let divisor = gcd(443, den);
+        (443 / divisor, den / divisor)
+    }
+
+    /// # Intent
+    /// Returns how far the running sum is from the nearest limit of TCALC,
+    /// which is `TCALC::MAX - sum` for a non-negative sum and
+    /// `sum - TCALC::MIN` for a negative one.
+    ///
+    /// Before the first sample the full `TCALC::MAX` is available.
+    #[must_use]
+    pub fn headroom(&self) -> TCALC {
+        match self.sum {
+            Some(sum) if sum < TCALC::ZERO => sum - TCALC::MIN,
+            Some(sum) => TCALC::MAX - sum,
+            None => TCALC::MAX,
+        }
+    }
+
+    /// # Intent
+    /// Re-checks at runtime that N * `T::MAX` fits in TCALC, so that code
+    /// handed an instance through generics can verify it defensively. This
+    /// is the only check left for instances built with the
+    /// `unchecked-capacity` feature or with SATURATING.
+    ///
+    /// # Errors
+    /// Returns `CapacityOverflow` if the running sum could overflow TCALC.
+    pub fn validate_depth(&self) -> Result<(), MovingAverageError> {
+        Self::check_capacity()
+    }
+
+    /// # Intent
+    /// Checks that the sums of squares behind the variance based methods,
+    /// `adaptive_average` and `standard_error`, can't overflow TCALC. This
+    /// needs `N * (T::MAX - T::MIN)²` to fit, which is much more than
+    /// construction checks, so it is left to callers of those methods.
+    ///
+    /// # Errors
+    /// Returns `SquaresOverflow` if the sum of squares could overflow TCALC.
+    pub fn validate_variance_depth(&self) -> Result<(), MovingAverageError> {
+        Self::check_squares_capacity()
+    }
+
+    const fn with_checked_capacity(num: TCALC) -> Self {
+        const {
+            assert!(
+                SATURATING
+                    || cfg!(feature = "unchecked-capacity")
+                    || fits_in_accumulator::<T, TCALC>(N),
+                "N * T.max() must fit in TCALC"
+            );
+        }
+        Self::with_num(num)
+    }
+
+    const fn with_num(num: TCALC) -> Self {
+        const {
+            assert!(
+                size_of::<TCALC>() > size_of::<T>(),
+                "TCALC must be larger than T"
+            );
+            assert!(N > 0, "N must be non-zero");
+        }
+        Self {
+            num,
+            sum: None,
+            buffer: HistoryBuffer::new(),
+            sentinel: None,
+            output_scale: None,
+            samples_since_change: 0,
+            last_output: None,
+            iir_alpha: None,
+            iir_state: None,
+            observed: 0,
+            ramp: None,
+            missing_policy: MissingPolicy::Hold,
+            active: N,
+        }
+    }
+
+    fn check_capacity() -> Result<(), MovingAverageError> {
+        check!(
+            fits_in_accumulator::<T, TCALC>(N),
+            MovingAverageError::CapacityOverflow
+        );
+        Ok(())
+    }
+
+    fn check_squares_capacity() -> Result<(), MovingAverageError> {
+        check!(
+            fits_squares_in_accumulator::<T, TCALC>(N),
+            MovingAverageError::SquaresOverflow
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    fn from_persisted(persisted: Persisted<T, TCALC, N>) -> Result<Self, MovingAverageError> {
+        let window = persisted.window;
+        check!(
+            !PREFILL || window.is_empty() || window.is_full(),
+            MovingAverageError::IncompleteWindow
+        );
+        Self::check_active_window(persisted.active)?;
+        if let Some((scale_num, scale_den)) = persisted.output_scale {
+            Self::new().check_output_scale(scale_num, scale_den)?;
+        }
+        let mut sut = Self::new();
+        if let Some((alpha_num, alpha_den)) = persisted.iir_alpha {
+            sut.set_iir_alpha(alpha_num, alpha_den)?;
+        }
+        sut.active = persisted.active;
+        sut.num = convert(persisted.active, MovingAverageError::CountOverflow)?;
+        for &value in &window {
+            sut.buffer.write(value);
+        }
+        if !window.is_empty() {
+            sut.sum = Some(sut.window_sum());
+        }
+        check!(
+            sut.num == persisted.num
+                && sut.sum == persisted.sum
+                && persisted.observed <= window.len(),
+            MovingAverageError::InconsistentState
+        );
+        sut.observed = persisted.observed;
+        sut.sentinel = persisted.sentinel;
+        sut.output_scale = persisted.output_scale;
+        sut.missing_policy = persisted.missing_policy;
+        Ok(sut)
+    }
+
+    fn check_active_window(window: usize) -> Result<(), MovingAverageError> {
+        check!(window > 0 && window <= N, MovingAverageError::InvalidLength);
+        Ok(())
+    }
+
+    fn resize_active_window(&mut self, window: usize) -> Result<(), MovingAverageError> {
+        Self::check_active_window(window)?;
+        self.num = convert(window, MovingAverageError::CountOverflow)?;
+        self.active = window;
+        if self.sum.is_some() {
+            self.sum = Some(self.window_sum());
+        }
+        Ok(())
+    }
+
+    fn with_output_scale(scale_num: TCALC, scale_den: TCALC) -> Result<Self, MovingAverageError> {
+        let sut = Self::new();
+        sut.check_output_scale(scale_num, scale_den)?;
+        Ok(Self {
+            output_scale: Some((scale_num, scale_den)),
+            ..sut
+        })
+    }
+
+    fn set_iir_alpha(
+        &mut self,
+        alpha_num: TCALC,
+        alpha_den: TCALC,
+    ) -> Result<(), MovingAverageError> {
+        Ewma::<T, TCALC>::check_alpha(alpha_num, alpha_den)?;
+        self.iir_alpha = Some((alpha_num, alpha_den));
+        Ok(())
+    }
+
+    fn start_ramp(&mut self, target: T, steps: usize) -> Result<(), MovingAverageError> {
+        let ramp_steps = convert(steps, MovingAverageError::CountOverflow)?;
+        self.ramp = (steps > 0).then(|| Ramp {
+            start: TCALC::from(self.current_average().unwrap_or(target)),
+            target: TCALC::from(target),
+            taken: TCALC::ZERO,
+            steps: ramp_steps,
+        });
+        Ok(())
+    }
+
+    fn check_output_scale(
+        &self,
+        scale_num: TCALC,
+        scale_den: TCALC,
+    ) -> Result<(), MovingAverageError> {
+        check!(
+            scale_num != TCALC::ZERO && scale_den != TCALC::ZERO,
+            MovingAverageError::ZeroScale
+        );
+        check!(
+            scale_num > TCALC::ZERO && scale_den > TCALC::ZERO,
+            MovingAverageError::NegativeScale
+        );
+        // Checked throughout, as with `unchecked-capacity` even
+        // `N * T::MAX` can overflow.
+        let scaled = |bound: T| {
+            self.num
+                .checked_mul(TCALC::from(bound))?
+                .checked_mul(scale_num)
+        };
+        check!(
+            scaled(T::MAX).is_some()
+                && scaled(T::MIN).is_some()
+                && self.num.checked_mul(scale_den).is_some(),
+            MovingAverageError::ScaleOverflow
+        );
+        Ok(())
+    }
+
+    /// Returns the number of samples in the active window, counting
+    /// copies from a fill.
+    fn window_len(&self) -> usize {
+        self.buffer.len().min(self.active)
+    }
+
+    /// Returns the samples in the active window, oldest first, skipping
+    /// any older samples kept in storage.
+    fn window(&self) -> impl Iterator<Item = &T> {
+        self.buffer
+            .oldest_ordered()
+            .skip(self.buffer.len() - self.window_len())
+    }
+
+    /// Sums the active window from scratch, in O(`active`).
+    fn window_sum(&self) -> TCALC {
+        self.window().fold(TCALC::ZERO, |sum, &value| {
+            if SATURATING {
+                sum.saturating_add(TCALC::from(value))
+            } else {
+                sum + TCALC::from(value)
+            }
+        })
+    }
+
+    /// Returns the real samples in the window, oldest first, skipping the
+    /// older copies from a fill.
+    fn real_samples(&self) -> impl Iterator<Item = &T> {
+        self.buffer
+            .oldest_ordered()
+            .skip(self.buffer.len() - self.len())
+    }
+
+    /// Returns a copy of the active window, oldest first, along with its
+    /// length.
+    fn ordered_window(&self) -> ([T; N], usize) {
+        let mut window = [T::ZERO; N];
+        for (slot, &value) in window.iter_mut().zip(self.window()) {
+            *slot = value;
+        }
+        (window, self.window_len())
+    }
+
+    /// Returns a copy of everything stored, oldest first, along with its
+    /// length, for rebuilding the buffer.
+    fn stored_window(&self) -> ([T; N], usize) {
+        let mut window = [T::ZERO; N];
+        for (slot, &value) in window.iter_mut().zip(self.buffer.oldest_ordered()) {
+            *slot = value;
+        }
+        (window, self.buffer.len())
+    }
+
+    /// Returns a sorted copy of the window along with its length.
+    fn sorted_window(&self) -> ([T; N], usize)
+    where
+        T: PartialOrd,
+    {
+        let (mut window, len) = self.ordered_window();
+        insertion_sort(&mut window[..len]);
+        (window, len)
+    }
+
+    fn sorted_calc_window(&self) -> Option<([TCALC; N], usize)> {
+        let len = self.window_len();
+        if len == 0 {
+            return None;
+        }
+        let mut values = [TCALC::ZERO; N];
+        for (slot, &value) in values.iter_mut().zip(self.window()) {
+            *slot = TCALC::from(value);
+        }
+        insertion_sort(&mut values[..len]);
+        Some((values, len))
+    }
+
+    fn median_and_mad(&self) -> Option<(TCALC, TCALC)> {
+        let (mut values, len) = self.sorted_calc_window()?;
+        let median = median_of_sorted(&values[..len]);
+        for value in &mut values[..len] {
+            *value = abs_diff(*value, median);
+        }
+        insertion_sort(&mut values[..len]);
+        Some((median, median_of_sorted(&values[..len])))
+    }
+
+    fn get_or_init_and_get_sum(&mut self, input: T) -> TCALC {
+        if let Some(sum) = self.sum {
+            sum
+        } else if PREFILL {
+            self.fill(input);
+            self.fill_sum(input)
+        } else {
+            TCALC::ZERO
+        }
+    }
+
+    fn insert_new_value_pop_oldest_value(&mut self, input: T) -> TCALC {
+        let remove = self.get_remove_value();
+        self.buffer.write(input);
+        self.observed = (self.observed + 1).min(N);
+        remove
+    }
+
+    /// The output stage of every `average` variant: the sentinel skip, the
+    /// running sum, the scaled and rounded narrowing, the ramp, and the
+    /// change tracking.
+    fn average_by(&mut self, input: T, rounding: Rounding) -> T {
+        let average = if self.sentinel == Some(input) {
+            self.sum
+                .map_or(input, |sum| self.narrow_average_by(sum, rounding))
+        } else {
+            let new_value = TCALC::from(input);
+            let prev_sum = self.get_or_init_and_get_sum(input);
+            let remove = self.insert_new_value_pop_oldest_value(input);
+            self.create_average(new_value, prev_sum, remove, rounding)
+        };
+        let output = self.next_ramp_output().unwrap_or(average);
+        self.track_change(output);
+        output
+    }
+
+    fn create_average(
+        &mut self,
+        new_value: TCALC,
+        prev_sum: TCALC,
+        remove: TCALC,
+        rounding: Rounding,
+    ) -> T {
+        let new_sum = if SATURATING {
+            prev_sum.saturating_sub(remove).saturating_add(new_value)
+        } else {
+            // Removing first keeps the intermediate within the sum of the window.
+            prev_sum - remove + new_value
+        };
+        self.sum = Some(new_sum);
+        self.narrow_average_by(new_sum, rounding)
+    }
+
+    fn fill_sum(&self, value: T) -> TCALC {
+        if SATURATING {
+            self.num.saturating_mul(TCALC::from(value))
+        } else {
+            self.num * TCALC::from(value)
+        }
+    }
+
+    fn narrow_average(&self, sum: TCALC) -> T {
+        self.narrow_average_by(sum, Rounding::TowardZero)
+    }
+
+    fn narrow_average_by(&self, sum: TCALC, rounding: Rounding) -> T {
+        Self::narrow(match rounding {
+            Rounding::TowardZero => self.scaled_average(sum),
+            Rounding::Nearest => self.rounded_average(sum),
+            Rounding::Floor => self.floored_average(sum),
+        })
+    }
+
+    fn scaled_average(&self, sum: TCALC) -> TCALC {
+        let (numerator, denominator) = self.scaled_fraction(sum);
+        numerator / denominator
+    }
+
+    fn scaled_fraction(&self, sum: TCALC) -> (TCALC, TCALC) {
+        self.scaled_fraction_over(sum, self.divisor())
+    }
+
+    fn scaled_fraction_over(&self, sum: TCALC, divisor: TCALC) -> (TCALC, TCALC) {
+        match self.output_scale {
+            Some((scale_num, scale_den)) => (sum * scale_num, divisor * scale_den),
+            None => (sum, divisor),
+        }
+    }
+
+    /// Divides `sum` with the remainder compared against the rest of the
+    /// denominator, rather than adding half the denominator first, so the
+    /// rounding can't overflow TCALC.
+    fn rounded_average(&self, sum: TCALC) -> TCALC {
+        let (numerator, denominator) = self.scaled_fraction(sum);
+        let quotient = numerator / denominator;
+        let remainder = numerator - quotient * denominator;
+        if remainder >= TCALC::ZERO {
+            if remainder >= denominator - remainder {
+                quotient + TCALC::ONE
+            } else {
+                quotient
+            }
+        } else if TCALC::ZERO - remainder >= denominator + remainder {
+            quotient - TCALC::ONE
+        } else {
+            quotient
+        }
+    }
+
+    fn floored_average(&self, sum: TCALC) -> TCALC {
+        let (numerator, denominator) = self.scaled_fraction(sum);
+        let quotient = numerator / denominator;
+        if quotient * denominator > numerator {
+            quotient - TCALC::ONE
+        } else {
+            quotient
+        }
+    }
+
+    #[cfg(not(feature = "saturating-narrow"))]
+    fn narrow(average_as_tcalc: TCALC) -> T {
+        unwrap_check(convert(
+            average_as_tcalc,
+            MovingAverageError::OutputOverflow,
+        ))
+    }
+
+    #[cfg(feature = "saturating-narrow")]
+    fn narrow(average_as_tcalc: TCALC) -> T {
+        Self::saturate(average_as_tcalc)
+    }
+
+    fn saturate(average_as_tcalc: TCALC) -> T {
+        T::try_from(average_as_tcalc).unwrap_or(if average_as_tcalc < TCALC::ZERO {
+            T::MIN
+        } else {
+            T::MAX
+        })
+    }
+
+    fn extrapolate(&self, newest: T) -> T {
+        let len = self.buffer.len();
+        let Some(&previous) = len
+            .checked_sub(2)
+            .and_then(|index| self.buffer.oldest_ordered().nth(index))
+        else {
+            return newest;
+        };
+        let (newest_calc, previous_calc) = (TCALC::from(newest), TCALC::from(previous));
+        let slope = abs_diff(newest_calc, previous_calc);
+        if newest_calc >= previous_calc {
+            Self::saturate(newest_calc + slope)
+        } else if slope > newest_calc - TCALC::from(T::MIN) {
+            T::MIN
+        } else {
+            Self::narrow(newest_calc - slope)
+        }
+    }
+
+    fn next_ramp_output(&mut self) -> Option<T> {
+        let mut ramp = self.ramp?;
+        ramp.taken = ramp.taken + TCALC::ONE;
+        let step = abs_diff(ramp.target, ramp.start) * ramp.taken / ramp.steps;
+        self.ramp = (ramp.taken != ramp.steps).then_some(ramp);
+        Some(Self::narrow(if ramp.target < ramp.start {
+            ramp.start - step
+        } else {
+            ramp.start + step
+        }))
+    }
+
+    /// Compares against the last output rather than recomputing the
+    /// previous average, to keep the division out of the hot path.
+    fn track_change(&mut self, average: T) {
+        self.samples_since_change = if self.last_output == Some(average) {
+            self.samples_since_change.saturating_add(1)
+        } else {
+            0
+        };
+        self.last_output = Some(average);
+    }
+
+    fn divisor(&self) -> TCALC {
+        if PREFILL || self.buffer.len() >= self.active {
+            self.num
+        } else {
+            unwrap_check(convert(
+                self.buffer.len(),
+                MovingAverageError::CountOverflow,
+            ))
+        }
+    }
+
+    fn get_remove_value(&self) -> TCALC {
+        if !PREFILL && self.buffer.len() < self.active {
+            return TCALC::ZERO;
+        }
+
+        #[cfg(test)]
+        assert!(
+            self.buffer.len() >= self.active,
+            "Buffer len {} shorter than the active window {}.",
+            self.buffer.len(),
+            self.active
+        );
+
+        let oldest = self.window().next().ok_or(MovingAverageError::EmptyWindow);
+        TCALC::from(*unwrap_check(oldest))
+    }
+}
+
+/// # Intent
+/// A `MovingAverage` whose window is full of real samples, as
+/// returned by `MovingAverage::into_warmed`. Its window holds no warm-up
+/// fill, so the average is always available and unbiased by it.
+pub struct WarmedAverage<
+    T,
+    TCALC,
+    const N: usize,
+    const PREFILL: bool = true,
+    const SATURATING: bool = false,
+>(MovingAverage<T, TCALC, N, PREFILL, SATURATING>)
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>;
+
+impl<T, TCALC, const N: usize, const PREFILL: bool, const SATURATING: bool>
+    WarmedAverage<T, TCALC, N, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T, as `MovingAverage::average`.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        self.0.average(input)
+    }
+
+    /// # Intent
+    /// Returns the average of the current window without inserting a
+    /// sample.
+    ///
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T, as `average`.
+    /// The sum is always present, as `into_warmed` checked it.
+    #[must_use]
+    pub fn current_average(&self) -> T {
+        unwrap_check(
+            self.0
+                .current_average()
+                .ok_or(MovingAverageError::EmptyWindow),
+        )
+    }
+
+    /// # Intent
+    /// Returns the underlying filter.
+    #[must_use]
+    pub fn into_inner(self) -> MovingAverage<T, TCALC, N, PREFILL, SATURATING> {
+        self.0
+    }
+}
+
+/// Returns the greatest common divisor of two integers.
+const fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Returns the middle of non-empty sorted `values`, or the lower of the two
+/// middle values for an even length, so it is always one of `values`.
+fn median_of_sorted<V: Copy>(values: &[V]) -> V {
+    values[(values.len() - 1) / 2]
+}
+
+/// Sorts without allocating, which is fast for the short windows used in
+/// embedded filters.
+fn insertion_sort<T: PartialOrd + Copy>(values: &mut [T]) {
+    for i in 1..values.len() {
+        let mut j = i;
+        while j > 0 && values[j - 1] > values[j] {
+            values.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[expect(clippy::cast_possible_truncation, reason = "Desirable in tests")]
+#[expect(clippy::cast_possible_wrap, reason = "Desirable in tests")]
+#[expect(clippy::manual_midpoint, reason = "Desirable in tests")]
+#[expect(clippy::expect_used, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::{
+        Diagnostics, Extremes, MissingPolicy, MovingAverage, Quality, Summary, WarmedAverage,
+    };
+    use crate::{Coefficients as _, Ewma, Filter, MovingAverageError};
+    use core::{
+        f64::consts::{FRAC_1_SQRT_2, PI},
+        hash::{Hash, Hasher as _},
+    };
+    use heapless::{HistoryBuffer, Vec};
+    use std::{collections::HashMap, hash::DefaultHasher};
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn given_new_moving_average_when_average_value_then_return_same_value() {
+        let mut sut = MovingAverage::<u32, u64, 1>::new();
+        let expected: u32 = 44;
+        assert_eq!(expected, sut.average(expected));
+    }
+
+    #[test]
+    fn given_two_item_moving_average_when_average_twice_value_then_return_average_of_those_values()
+    {
+        let mut sut = MovingAverage::<u32, u64, 2>::new();
+        let first: u32 = 22;
+        let second: u32 = 44;
+        let expected = (first + second) / 2;
+        let _ = sut.average(first);
+        assert_eq!(expected, sut.average(second));
+    }
+
+    #[test]
+    fn given_two_item_moving_average_when_average_called_thrice_then_return_average_of_the_last_two_values()
+     {
+        let mut sut = MovingAverage::<u32, u64, 2>::new();
+        let first: u32 = 22;
+        let second: u32 = 44;
+        let third: u32 = 66;
+        let expected = (second + third) / 2;
+        let _ = sut.average(first);
+        let _ = sut.average(second);
+        assert_eq!(expected, sut.average(third));
+    }
+
+    #[test]
+    fn given_two_signed_item_moving_average_when_average_called_thrice_then_return_average_of_the_last_two_values()
+     {
+        let mut sut = MovingAverage::<i32, i64, 2>::new();
+        let first: i32 = -22;
+        let second: i32 = 44;
+        let third: i32 = -66;
+        let expected = (second + third) / 2_i32;
+        let _ = sut.average(first);
+        let _ = sut.average(second);
+        assert_eq!(expected, sut.average(third));
+    }
+
+    #[test]
+    fn given_large_item_moving_average_when_average_called_thrice_then_return_average_of_the_last_two_values()
+     {
+        const DEPTH: usize = 128;
+        let mut sut = MovingAverage::<i32, i64, DEPTH>::new();
+        let first: i32 = -22;
+        let second: i32 = 44;
+        let third: i32 = -66;
+        let expected = (first + second + third + (((DEPTH as i32) - 3_i32) * first)) / DEPTH as i32;
+        let _ = sut.average(first);
+        let _ = sut.average(second);
+        assert_eq!(expected, sut.average(third));
+    }
+
+    // Issue 1 replication case
+    // https://github.com/Radiator-Labs/no-std-moving-average-rs/issues/1
+    #[test]
+    fn given_enough_items_to_roll_over_when_average_called_then_return_average_of_the_correct_set_of_values()
+     {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let sequence = [
             100_u16, 200_u16, 300_u16, 400_u16, 100_u16, 200_u16, 300_u16, 400_u16, 100_u16,
             200_u16, 300_u16, 400_u16,
         ];
@@ -309,20 +2662,2311 @@ mod tests {
             250_u16, 250_u16, 250_u16,
         ];
 
-        for (i, val) in sequence.iter().enumerate() {
-            let avg = sut.average(*val);
-            assert_eq!(
-                expected[i], avg,
-                "Failed at {i}, is {avg}, should be {}",
-                expected[i]
-            );
+        for (i, val) in sequence.iter().enumerate() {
+            let avg = sut.average(*val);
+            assert_eq!(
+                expected[i], avg,
+                "Failed at {i}, is {avg}, should be {}",
+                expected[i]
+            );
+        }
+    }
+
+    #[test]
+    fn given_output_scale_when_average_then_return_unscaled_average_times_ratio() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_with_output_scale(3_u32, 2_u32);
+        let mut reference = MovingAverage::<u16, u32, 4>::new();
+        for val in [8_u16, 16_u16, 24_u16, 32_u16] {
+            assert_eq!(reference.average(val) * 3 / 2, sut.average(val));
+        }
+    }
+
+    #[test]
+    fn given_output_scale_when_average_then_multiply_happens_before_divide() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_with_output_scale(3_u32, 2_u32);
+        for val in [10_u16, 11_u16, 12_u16] {
+            let _ = sut.average(val);
+        }
+        // (46 * 3) / (4 * 2) = 17, while the scaled truncated average is 11 * 3 / 2 = 16
+        assert_eq!(17_u16, sut.average(13_u16));
+        assert_eq!(Some(17_u16), sut.current_average());
+    }
+
+    #[cfg(not(feature = "saturating-narrow"))]
+    #[test]
+    #[should_panic(expected = "average must fit in T")]
+    fn confirm_out_of_range_average_panics() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new_with_output_scale(2_u16, 1_u16);
+        let _ = sut.average(200_u8);
+    }
+
+    #[cfg(feature = "saturating-narrow")]
+    #[test]
+    fn given_saturating_narrow_when_average_status_out_of_range_then_saturated() {
+        let mut sut = MovingAverage::<u8, u16, 2>::new_with_output_scale(2_u16, 1_u16);
+        assert_eq!((u8::MAX, Quality::Saturated), sut.average_status(200_u8));
+        assert_eq!((u8::MAX, Quality::Saturated), sut.average_status(200_u8));
+        assert_eq!((200_u8, Quality::Valid), sut.average_status(0_u8));
+    }
+
+    #[cfg(feature = "saturating-narrow")]
+    #[test]
+    fn given_saturating_narrow_when_average_out_of_range_then_saturate_to_t_max() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new_with_output_scale(2_u16, 1_u16);
+        assert_eq!(u8::MAX, sut.average(200_u8));
+        assert_eq!(Some(u8::MAX), sut.current_average());
+        sut.fill(25_u8);
+        assert_eq!(50_u8, sut.average(25_u8));
+    }
+
+    #[cfg(feature = "saturating-narrow")]
+    #[test]
+    fn given_saturating_narrow_when_average_out_of_range_then_saturate_to_t_min() {
+        let mut sut = MovingAverage::<i8, i16, 4>::new_with_output_scale(3_i16, 1_i16);
+        assert_eq!(i8::MIN, sut.average(-100_i8));
+        assert_eq!(Some(i8::MIN), sut.current_average());
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "N * T.max() * scale_num and N * scale_den must fit in TCALC")]
+    fn confirm_n_times_t_max_times_scale_num_fits_in_tcalc() {
+        let _sut = MovingAverage::<u8, u16, 256>::new_with_output_scale(2_u16, 1_u16);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "N * scale_den must fit in TCALC")]
+    fn confirm_n_times_scale_den_fits_in_tcalc() {
+        let _sut = MovingAverage::<u8, u16, 256>::new_with_output_scale(1_u16, 256_u16);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "scale_den must be non-zero")]
+    fn confirm_scale_den_is_non_zero() {
+        let _sut = MovingAverage::<u8, u16, 4>::new_with_output_scale(1_u16, 0_u16);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "scale_num and scale_den must be non-negative")]
+    fn confirm_scale_num_of_minus_one_panics_with_negative_scale() {
+        let _sut = MovingAverage::<i8, i16, 4>::new_with_output_scale(-1_i16, 1_i16);
+    }
+
+    #[test]
+    fn given_negative_or_overflowing_scale_when_check_output_scale_then_return_error() {
+        let sut = MovingAverage::<i8, i16, 4>::new();
+        assert_eq!(
+            Err(MovingAverageError::NegativeScale),
+            sut.check_output_scale(-1_i16, 1_i16)
+        );
+        assert_eq!(
+            Err(MovingAverageError::NegativeScale),
+            sut.check_output_scale(1_i16, -1_i16)
+        );
+        assert_eq!(
+            Err(MovingAverageError::ScaleOverflow),
+            sut.check_output_scale(i16::MAX, 1_i16)
+        );
+        assert_eq!(Ok(()), sut.check_output_scale(63_i16, 1_i16));
+    }
+
+    #[cfg(all(feature = "unchecked-capacity", not(feature = "defmt-checks")))]
+    #[test]
+    #[should_panic(expected = "N * T.max() * scale_num and N * scale_den must fit in TCALC")]
+    fn given_unchecked_capacity_when_n_times_t_max_overflows_then_scale_check_reports_it() {
+        let _sut = MovingAverage::<u8, u16, 512>::new_with_output_scale(1_u16, 1_u16);
+    }
+
+    #[test]
+    fn given_millivolt_map_when_average_mapped_then_return_mapped_average() {
+        let mut sut = MovingAverage::<u16, u32, 2>::new();
+        let to_millivolts = |counts: u16| u32::from(counts) * 3300_u32 / 4096_u32;
+        assert_eq!(3300_u32, sut.average_mapped(4096_u16, to_millivolts));
+        assert_eq!(2475_u32, sut.average_mapped(2048_u16, to_millivolts));
+        assert_eq!(Some(3072_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_square_wave_when_average_then_iir_then_ripple_is_below_moving_average() {
+        let mut plain = MovingAverage::<u16, u32, 3>::new();
+        let mut cascaded = MovingAverage::<u16, u32, 3>::new();
+        cascaded.enable_iir(1_u32, 4_u32);
+        let (mut plain_min, mut plain_max) = (u16::MAX, u16::MIN);
+        let (mut cascaded_min, mut cascaded_max) = (u16::MAX, u16::MIN);
+        for i in 0..40_usize {
+            let val = if i % 2 == 0 { 100_u16 } else { 200_u16 };
+            let plain_avg = plain.average(val);
+            let cascaded_avg = cascaded.average_then_iir(val);
+            if i >= 30 {
+                plain_min = plain_min.min(plain_avg);
+                plain_max = plain_max.max(plain_avg);
+                cascaded_min = cascaded_min.min(cascaded_avg);
+                cascaded_max = cascaded_max.max(cascaded_avg);
+            }
+        }
+        assert_eq!(33_u16, plain_max - plain_min);
+        assert!(cascaded_max - cascaded_min < plain_max - plain_min);
+    }
+
+    #[test]
+    fn given_falling_step_when_average_then_iir_then_state_follows_in_fractions() {
+        let mut sut = MovingAverage::<u16, u32, 1>::new();
+        sut.enable_iir(1_u32, 2_u32);
+        assert_eq!(200_u16, sut.average_then_iir(200_u16));
+        assert_eq!(150_u16, sut.average_then_iir(100_u16));
+        assert_eq!(125_u16, sut.average_then_iir(100_u16));
+    }
+
+    #[test]
+    fn given_iir_not_enabled_when_average_then_iir_then_return_average() {
+        let mut sut = MovingAverage::<u16, u32, 2>::new();
+        let _ = sut.average_then_iir(200_u16);
+        assert_eq!(150_u16, sut.average_then_iir(100_u16));
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "alpha_num / alpha_den must be in (0, 1]")]
+    fn confirm_iir_alpha_of_zero_denominator_panics() {
+        let mut sut = MovingAverage::<u16, u32, 2>::new();
+        sut.enable_iir(1_u32, 0_u32);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "(T.max() - T.min()) * alpha_num must fit in TCALC")]
+    fn confirm_iir_alpha_num_times_t_range_fits_in_tcalc() {
+        let mut sut = MovingAverage::<u8, u16, 2>::new();
+        sut.enable_iir(0x102_u16, 0x200_u16);
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_bad_factor_when_try_enable_iir_then_return_error_and_keep_factor() {
+        let mut sut = MovingAverage::<u16, u32, 1>::new();
+        assert_eq!(Ok(()), sut.try_enable_iir(1_u32, 2_u32));
+        assert_eq!(
+            Err(MovingAverageError::InvalidAlpha),
+            sut.try_enable_iir(3_u32, 2_u32)
+        );
+        let _ = sut.average_then_iir(200_u16);
+        assert_eq!(150_u16, sut.average_then_iir(100_u16));
+    }
+
+    #[test]
+    fn given_count_of_n_when_average_repeated_then_match_fill() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let mut filled = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16] {
+            let _ = sut.average(val);
+            let _ = filled.average(val);
+        }
+        filled.fill(80_u16);
+        assert_eq!(80_u16, sut.average_repeated(80_u16, 4));
+        assert_eq!(filled.current_average(), sut.current_average());
+        assert!(sut.buffer().iter().eq(filled.buffer().iter()));
+    }
+
+    #[test]
+    fn given_shorter_active_window_when_average_repeated_then_match_loop_after_widening() {
+        let mut sut = MovingAverage::<u16, u32, 6>::new();
+        let mut looped = MovingAverage::<u16, u32, 6>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16, 50_u16, 60_u16] {
+            let _ = sut.average(val);
+            let _ = looped.average(val);
+        }
+        sut.set_active_window(2);
+        looped.set_active_window(2);
+        let mut expected = 0_u16;
+        for count in [3_usize, 40_usize] {
+            for _ in 0..count {
+                expected = looped.average(90_u16);
+            }
+            assert_eq!(expected, sut.average_repeated(90_u16, count));
+            assert!(sut == looped, "Failed at {count}");
+            assert_eq!(looped.len(), sut.len());
+            assert_eq!(looped.samples_since_change(), sut.samples_since_change());
+            sut.set_active_window(6);
+            looped.set_active_window(6);
+            assert_eq!(looped.current_average(), sut.current_average());
+            assert_eq!(looped.len(), sut.len());
+            sut.set_active_window(2);
+            looped.set_active_window(2);
+        }
+    }
+
+    #[test]
+    fn given_ramp_when_average_repeated_then_match_loop() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let mut looped = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(0_u16);
+        let _ = looped.average(0_u16);
+        sut.transition_to(1000_u16, 10);
+        looped.transition_to(1000_u16, 10);
+        let mut expected = 0_u16;
+        for _ in 0..7_usize {
+            expected = looped.average(20_u16);
+        }
+        assert_eq!(expected, sut.average_repeated(20_u16, 7));
+        assert!(sut == looped);
+        for _ in 0..20_usize {
+            expected = looped.average(20_u16);
+        }
+        assert_eq!(expected, sut.average_repeated(20_u16, 20));
+        assert!(sut == looped);
+        assert_eq!(looped.samples_since_change(), sut.samples_since_change());
+    }
+
+    #[test]
+    fn given_count_below_n_when_average_repeated_then_match_loop() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let mut looped = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(10_u16);
+        let _ = looped.average(10_u16);
+        assert_eq!(10_u16, sut.average_repeated(90_u16, 0));
+        let mut expected = 0_u16;
+        for _ in 0..3_usize {
+            expected = looped.average(90_u16);
+        }
+        assert_eq!(expected, sut.average_repeated(90_u16, 3));
+        assert_eq!(70_u16, expected);
+    }
+
+    #[test]
+    fn given_filled_window_when_average_then_window_is_all_fill_value() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(100_u16);
+        let _ = sut.average(200_u16);
+        sut.fill(40_u16);
+        assert_eq!(Some(40_u16), sut.current_average());
+        assert_eq!(50_u16, sut.average(80_u16));
+    }
+
+    #[test]
+    fn given_samples_when_reset_then_next_average_is_first_new_sample() {
+        let mut sut = MovingAverage::<u16, u32, 8>::new();
+        for val in [100_u16, 200_u16, 300_u16] {
+            let _ = sut.average(val);
+        }
+        sut.reset();
+        assert!(sut.is_empty());
+        assert_eq!(None, sut.current_average());
+        assert_eq!(40_u16, sut.average(40_u16));
+        assert_eq!(8, sut.buffer().len());
+        assert!(sut.buffer().oldest_ordered().all(|&value| value == 40_u16));
+        assert_eq!(41_u16, sut.average(48_u16));
+    }
+
+    #[test]
+    fn given_varied_window_when_reseed_preserving_average_then_average_is_unchanged() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 35_u16, 7_u16] {
+            let _ = sut.average(val);
+        }
+        let before = sut.current_average();
+        sut.reseed_preserving_average();
+        assert_eq!(before, sut.current_average());
+        assert_eq!(Some(&18_u16), sut.buffer().oldest_ordered().next());
+        assert_eq!(18_u16, sut.average(18_u16));
+    }
+
+    #[test]
+    fn given_new_moving_average_when_reseed_preserving_average_then_nothing_changes() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        sut.reseed_preserving_average();
+        assert_eq!(None, sut.current_average());
+        assert_eq!(0, sut.buffer().len());
+    }
+
+    #[test]
+    fn given_ramp_interrupted_by_step_when_average_reset_on_jump_then_reconverge_immediately() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let mut reference = MovingAverage::<u16, u32, 4>::new();
+        for val in [100_u16, 104_u16, 108_u16, 112_u16] {
+            assert_eq!(
+                reference.average(val),
+                sut.average_reset_on_jump(val, 50_u16)
+            );
+        }
+        assert_eq!(1_000_u16, sut.average_reset_on_jump(1_000_u16, 50_u16));
+        assert_eq!(1_001_u16, sut.average_reset_on_jump(1_004_u16, 50_u16));
+        assert_eq!(1_003_u16, sut.average_reset_on_jump(1_008_u16, 50_u16));
+    }
+
+    #[test]
+    fn given_new_moving_average_when_average_reset_on_jump_then_return_input() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(500_u16, sut.average_reset_on_jump(500_u16, 1_u16));
+    }
+
+    #[test]
+    fn given_known_window_when_summary_then_return_mean_min_and_max() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new();
+        for val in [7_i16, -3_i16, 12_i16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(
+            Summary {
+                mean: 4_i16,
+                min: -3_i16,
+                max: 12_i16
+            },
+            sut.summary(0_i16)
+        );
+        assert_eq!(
+            Summary {
+                mean: 6_i16,
+                min: -3_i16,
+                max: 15_i16
+            },
+            sut.summary(15_i16)
+        );
+    }
+
+    #[test]
+    fn given_output_scale_or_ramp_when_summary_then_only_mean_is_affected() {
+        let mut scaled = MovingAverage::<u16, u32, 4>::new_with_output_scale(10_u32, 1_u32);
+        assert_eq!(
+            Summary {
+                mean: 1000_u16,
+                min: 100_u16,
+                max: 100_u16
+            },
+            scaled.summary(100_u16)
+        );
+        let mut ramped = MovingAverage::<u16, u32, 4>::new();
+        let _ = ramped.average(100_u16);
+        ramped.transition_to(1000_u16, 4);
+        assert_eq!(
+            Summary {
+                mean: 325_u16,
+                min: 100_u16,
+                max: 100_u16
+            },
+            ramped.summary(100_u16)
+        );
+    }
+
+    #[test]
+    fn given_new_moving_average_when_summary_then_all_fields_are_input() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new();
+        assert_eq!(
+            Summary {
+                mean: 9_u8,
+                min: 9_u8,
+                max: 9_u8
+            },
+            sut.summary(9_u8)
+        );
+    }
+
+    #[test]
+    fn given_quiet_signal_when_adaptive_average_then_return_average() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(100_u16, sut.adaptive_average(100_u16, 4_u32));
+        assert_eq!(100_u16, sut.adaptive_average(101_u16, 4_u32));
+        assert_eq!(100_u16, sut.adaptive_average(99_u16, 4_u32));
+    }
+
+    #[test]
+    fn given_fast_transient_when_adaptive_average_then_return_raw_input() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [100_u16, 101_u16, 99_u16] {
+            let _ = sut.adaptive_average(val, 4_u32);
+        }
+        assert_eq!(200_u16, sut.adaptive_average(200_u16, 4_u32));
+        assert_eq!(Some(125_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_unsigned_item_in_signed_calculation_when_average_values_near_max_then_return_average()
+    {
+        let mut sut = MovingAverage::<u16, i32, 4>::new();
+        let _ = sut.average(u16::MAX);
+        let _ = sut.average(u16::MAX - 1);
+        let _ = sut.average(u16::MAX - 2);
+        assert_eq!(u16::MAX - 2, sut.average(u16::MAX - 5));
+        assert_eq!(u16::MAX - 4, sut.average(u16::MAX - 6));
+    }
+
+    #[test]
+    fn given_unsigned_item_in_signed_calculation_at_max_depth_when_average_max_then_return_max() {
+        let mut sut = MovingAverage::<u16, i32, 0x8000>::new();
+        let _ = sut.average(1_u16);
+        for _ in 0..0x8000_usize {
+            let _ = sut.average(u16::MAX);
+        }
+        assert_eq!(u16::MAX, sut.average(u16::MAX));
+    }
+
+    #[test]
+    fn given_signed_item_at_max_depth_when_average_min_then_return_min() {
+        let mut sut = MovingAverage::<i8, i16, 256>::new();
+        let _ = sut.average(i8::MIN);
+        assert_eq!(i8::MIN, sut.average(i8::MIN));
+        assert_eq!(i8::MIN, sut.average(i8::MIN));
+    }
+
+    #[test]
+    fn given_prefill_when_average_first_n_then_return_average_biased_to_first_value() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let sequence = [100_u16, 200_u16, 300_u16, 400_u16, 500_u16];
+        let expected = [100_u16, 125_u16, 175_u16, 250_u16, 350_u16];
+        for (i, val) in sequence.iter().enumerate() {
+            assert_eq!(expected[i], sut.average(*val), "Failed at {i}");
+        }
+    }
+
+    #[test]
+    fn given_no_prefill_when_average_first_n_then_return_average_of_samples_seen() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        let sequence = [100_u16, 200_u16, 300_u16, 400_u16, 500_u16];
+        let expected = [100_u16, 150_u16, 200_u16, 250_u16, 350_u16];
+        for (i, val) in sequence.iter().enumerate() {
+            assert_eq!(expected[i], sut.average(*val), "Failed at {i}");
+            assert_eq!((i + 1).min(4), sut.buffer().len(), "Failed at {i}");
+        }
+    }
+
+    #[test]
+    fn given_no_prefill_when_partially_filled_then_statistics_use_samples_seen() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        let _ = sut.average(10_u16);
+        assert_eq!(None, sut.mean_consecutive_diff());
+        let _ = sut.average(16_u16);
+        assert_eq!(Some(13_u16), sut.current_average());
+        assert_eq!(Some(6_u32), sut.mean_consecutive_diff());
+        let mut window = [0_u16; 4];
+        assert_eq!(12_u16, sut.average_and_window(10_u16, &mut window));
+        assert_eq!([10_u16, 16_u16, 0_u16, 0_u16], window);
+    }
+
+    #[test]
+    fn given_valid_depth_when_validate_depth_then_return_ok() {
+        let sut = MovingAverage::<u8, u16, 256>::new();
+        assert_eq!(Ok(()), sut.validate_depth());
+    }
+
+    #[test]
+    fn given_instance_built_without_checks_when_validate_depth_then_return_capacity_overflow() {
+        let sut = MovingAverage::<u8, u16, 512> {
+            num: 512_u16,
+            sum: None,
+            buffer: HistoryBuffer::new(),
+            sentinel: None,
+            output_scale: None,
+            samples_since_change: 0,
+            last_output: None,
+            iir_alpha: None,
+            iir_state: None,
+            observed: 0,
+            ramp: None,
+            missing_policy: MissingPolicy::Hold,
+            active: 512,
+        };
+        assert_eq!(
+            Err(MovingAverageError::CapacityOverflow),
+            sut.validate_depth()
+        );
+    }
+
+    #[test]
+    fn given_identical_sequences_when_hash_then_hashes_match() {
+        let mut first = MovingAverage::<u16, u32, 3>::new();
+        let mut second = MovingAverage::<u16, u32, 3>::new();
+        for val in [5_u16, 6_u16, 7_u16, 8_u16] {
+            let _ = first.average(val);
+            let _ = second.average(val);
+        }
+        assert_eq!(hash_of(&first), hash_of(&second));
+        let _ = second.average(9_u16);
+        assert_ne!(hash_of(&first), hash_of(&second));
+    }
+
+    #[test]
+    fn given_same_window_at_different_write_positions_when_hash_then_hashes_match() {
+        let mut first = MovingAverage::<u16, u32, 2>::new();
+        let mut second = MovingAverage::<u16, u32, 2>::new();
+        first.fill(4_u16);
+        let _ = first.average(4_u16);
+        second.fill(4_u16);
+        assert_eq!(hash_of(&first), hash_of(&second));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn given_filter_and_its_results_when_logged_with_defmt_then_bytes_are_written() {
+        use crate::test_logger::BYTES_WRITTEN;
+        use core::sync::atomic::Ordering;
+
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let before = BYTES_WRITTEN.load(Ordering::Relaxed);
+        defmt::println!("{}", sut);
+        let summary = sut.summary(30_u16);
+        defmt::println!("{} {} {}", sut, summary, MovingAverageError::EmptyWindow);
+        assert!(BYTES_WRITTEN.load(Ordering::Relaxed) > before);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_filter_when_serialized_and_restored_then_continues_as_the_original() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_with_sentinel(0xFFFF_u16);
+        sut.set_active_window(3);
+        sut.enable_iir(1_u32, 2_u32);
+        for val in [10_u16, 20_u16, 30_u16, 40_u16, 50_u16] {
+            let _ = sut.average(val);
+        }
+        let json = serde_json::to_string(&sut).expect("Filter serializes");
+        let mut restored: MovingAverage<u16, u32, 4> =
+            serde_json::from_str(&json).expect("Filter deserializes");
+        assert!(sut == restored);
+        assert_eq!(sut.len(), restored.len());
+        for val in [60_u16, 0xFFFF_u16, 70_u16] {
+            assert_eq!(sut.average(val), restored.average(val));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_inconsistent_record_when_deserialized_then_return_error() {
+        let partial = r#"{"num":4,"sum":30,"window":[10,20],"observed":2,"active":4,"sentinel":null,"output_scale":null,"missing_policy":"Hold","iir_alpha":null}"#;
+        let partial_error = serde_json::from_str::<MovingAverage<u16, u32, 4>>(partial)
+            .expect_err("A partial window is rejected with PREFILL");
+        assert!(
+            partial_error
+                .to_string()
+                .contains("seed must hold N samples")
+        );
+        let wrong_sum = r#"{"num":2,"sum":31,"window":[10,20],"observed":2,"active":2,"sentinel":null,"output_scale":null,"missing_policy":"Hold","iir_alpha":null}"#;
+        let sum_error = serde_json::from_str::<MovingAverage<u16, u32, 2>>(wrong_sum)
+            .expect_err("A sum that doesn't match the window is rejected");
+        assert!(sum_error.to_string().contains("must match the window"));
+        let too_long = r#"{"num":2,"sum":60,"window":[10,20,30],"observed":3,"active":2,"sentinel":null,"output_scale":null,"missing_policy":"Hold","iir_alpha":null}"#;
+        assert!(serde_json::from_str::<MovingAverage<u16, u32, 2>>(too_long).is_err());
+        let bad_alpha = r#"{"num":2,"sum":30,"window":[10,20],"observed":2,"active":2,"sentinel":null,"output_scale":null,"missing_policy":"Hold","iir_alpha":[3,2]}"#;
+        let alpha_error = serde_json::from_str::<MovingAverage<u16, u32, 2>>(bad_alpha)
+            .expect_err("An IIR factor above one is rejected");
+        assert!(alpha_error.to_string().contains("must be in (0, 1]"));
+    }
+
+    #[test]
+    fn given_same_window_but_different_settings_when_compared_then_filters_differ() {
+        let plain = MovingAverage::<u16, u32, 4>::new();
+        let scaled = MovingAverage::<u16, u32, 4>::new_with_output_scale(10_u32, 1_u32);
+        let sentinel = MovingAverage::<u16, u32, 4>::new_with_sentinel(0xFFFF_u16);
+        let mut narrowed = MovingAverage::<u16, u32, 4>::new();
+        narrowed.set_active_window(2);
+        let mut extrapolating = MovingAverage::<u16, u32, 4>::new();
+        extrapolating.set_missing_policy(MissingPolicy::Extrapolate);
+        let mut ramping = MovingAverage::<u16, u32, 4>::new();
+        ramping.transition_to(100_u16, 4);
+        for other in [&scaled, &sentinel, &narrowed, &extrapolating, &ramping] {
+            assert!(plain != *other);
+            assert_ne!(hash_of(&plain), hash_of(other));
+        }
+    }
+
+    #[test]
+    fn given_logically_equal_filters_when_used_as_map_keys_then_share_an_entry() {
+        let mut cache = HashMap::new();
+        let mut first = MovingAverage::<u16, u32, 3>::new();
+        for val in 1_u16..=5_u16 {
+            let _ = first.average(val);
+        }
+        let second = MovingAverage::<u16, u32, 3>::from_slice(&[3_u16, 4_u16, 5_u16]);
+        assert_eq!(first, second);
+        assert_eq!(None, cache.insert(first, 0x2A_u32));
+        assert_eq!(Some(&0x2A_u32), cache.get(&second));
+        assert_eq!(Some(0x2A_u32), cache.insert(second, 7_u32));
+        assert_eq!(1, cache.len());
+    }
+
+    #[test]
+    fn given_impulse_coefficients_when_correlate_then_select_single_sample() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new();
+        assert_eq!(None, sut.correlate(&[1_i32; 4]));
+        for val in [3_i16, -5_i16, 7_i16, 11_i16, 13_i16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(-5_i32), sut.correlate(&[1_i32, 0_i32, 0_i32, 0_i32]));
+        assert_eq!(Some(26_i32), sut.correlate(&[0_i32, 0_i32, 0_i32, 2_i32]));
+    }
+
+    #[test]
+    fn given_no_prefill_when_correlate_before_full_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 2, false>::new();
+        let _ = sut.average(3_u16);
+        assert_eq!(None, sut.correlate(&[1_u32, 1_u32]));
+        let _ = sut.average(4_u16);
+        assert_eq!(Some(11_u32), sut.correlate(&[1_u32, 2_u32]));
+    }
+
+    #[test]
+    fn given_constant_input_when_average_then_samples_since_change_grows() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(50_u16);
+        assert_eq!(0, sut.samples_since_change());
+        for expected in 1..=5_u32 {
+            let _ = sut.average(50_u16);
+            assert_eq!(expected, sut.samples_since_change());
+        }
+    }
+
+    #[test]
+    fn given_changing_input_when_average_then_samples_since_change_resets() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for _ in 0..3_usize {
+            let _ = sut.average(50_u16);
+        }
+        assert_eq!(2, sut.samples_since_change());
+        let _ = sut.average(90_u16);
+        assert_eq!(0, sut.samples_since_change());
+    }
+
+    #[test]
+    fn given_counter_at_max_when_average_unchanged_then_samples_since_change_saturates() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(50_u16);
+        sut.samples_since_change = u32::MAX;
+        let _ = sut.average(50_u16);
+        assert_eq!(u32::MAX, sut.samples_since_change());
+    }
+
+    #[test]
+    fn given_full_vec_when_try_from_then_window_is_vec_contents() {
+        let samples: Vec<u16, 3> = [10_u16, 20_u16, 60_u16].into_iter().collect();
+        let mut sut = MovingAverage::<u16, u32, 3>::try_from(samples).expect("Vec is full");
+        assert_eq!(Some(30_u16), sut.current_average());
+        assert_eq!(Some(&10_u16), sut.buffer().oldest_ordered().next());
+        assert_eq!(40_u16, sut.average(40_u16));
+    }
+
+    #[test]
+    fn given_partial_vec_when_try_from_then_prefill_errors_and_lazy_accepts() {
+        let samples: Vec<u16, 3> = [10_u16, 20_u16].into_iter().collect();
+        assert_eq!(
+            Some(MovingAverageError::IncompleteWindow),
+            MovingAverage::<u16, u32, 3>::try_from(samples.clone()).err()
+        );
+        let mut sut =
+            MovingAverage::<u16, u32, 3, false>::try_from(samples).expect("Lazy accepts partial");
+        assert_eq!(Some(15_u16), sut.current_average());
+        assert_eq!(20_u16, sut.average(30_u16));
+        assert_eq!(30_u16, sut.average(40_u16));
+    }
+
+    #[test]
+    fn given_known_window_when_average_from_offset_then_wrap_around_to_oldest() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.average_from_offset(0, 1));
+        for val in [1_u16, 10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(15_u16), sut.average_from_offset(0, 2));
+        assert_eq!(Some(35_u16), sut.average_from_offset(2, 2));
+        assert_eq!(Some(25_u16), sut.average_from_offset(3, 2));
+        assert_eq!(Some(25_u16), sut.average_from_offset(1, 4));
+    }
+
+    #[test]
+    fn given_out_of_range_view_when_average_from_offset_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(7_u16);
+        assert_eq!(None, sut.average_from_offset(0, 0));
+        assert_eq!(None, sut.average_from_offset(0, 5));
+        assert_eq!(None, sut.average_from_offset(4, 1));
+    }
+
+    #[test]
+    fn given_known_extremes_when_extremes_with_position_then_return_ages() {
+        let mut sut = MovingAverage::<i16, i32, 5>::new();
+        assert_eq!(None, sut.extremes_with_position());
+        for val in [0_i16, 90_i16, 4_i16, -30_i16, 7_i16, 5_i16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(
+            Some(Extremes {
+                min: -30_i16,
+                min_age: 2,
+                max: 90_i16,
+                max_age: 4,
+            }),
+            sut.extremes_with_position()
+        );
+    }
+
+    #[test]
+    fn given_repeated_extreme_when_extremes_with_position_then_report_newest() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [9_u16, 1_u16, 9_u16, 1_u16] {
+            let _ = sut.average(val);
+        }
+        let extremes = sut.extremes_with_position().expect("Window has samples");
+        assert_eq!((1_u16, 0), (extremes.min, extremes.min_age));
+        assert_eq!((9_u16, 1), (extremes.max, extremes.max_age));
+    }
+
+    #[test]
+    fn given_known_top_values_when_top_k_average_then_return_mean_of_largest() {
+        let mut sut = MovingAverage::<u16, u32, 5>::new();
+        assert_eq!(None, sut.top_k_average(2));
+        for val in [0_u16, 40_u16, 5_u16, 90_u16, 10_u16, 70_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(None, sut.top_k_average(0));
+        assert_eq!(Some(90_u16), sut.top_k_average(1));
+        assert_eq!(Some(80_u16), sut.top_k_average(2));
+        assert_eq!(Some(43_u16), sut.top_k_average(9));
+    }
+
+    #[test]
+    fn given_preloaded_filter_when_first_average_then_do_not_prefill() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_preloaded(100_u16);
+        assert_eq!(Some(100_u16), sut.current_average());
+        assert_eq!(125_u16, sut.average(200_u16));
+        assert_eq!(
+            [100_u16, 100_u16, 100_u16, 200_u16],
+            *sut.buffer()
+                .oldest_ordered()
+                .copied()
+                .collect::<Vec<u16, 4>>()
+        );
+    }
+
+    #[test]
+    fn given_known_spread_when_standard_error_then_return_std_dev_over_root_n() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.standard_error());
+        for val in [10_u16, 30_u16, 10_u16, 30_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(5_u32), sut.standard_error());
+        for val in [20_u16, 40_u16, 60_u16, 80_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(11_u32), sut.standard_error());
+    }
+
+    #[test]
+    fn given_fewer_than_n_samples_when_into_warmed_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        assert!(MovingAverage::<u16, u32, 3>::new().into_warmed().is_none());
+        let _ = sut.average(10_u16);
+        let _ = sut.average(20_u16);
+        assert!(sut.into_warmed().is_none());
+        assert!(
+            MovingAverage::<u16, u32, 3>::new_preloaded(5_u16)
+                .into_warmed()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn given_n_samples_when_into_warmed_then_average_is_infallible() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        for val in [10_u16, 20_u16, 30_u16] {
+            let _ = sut.average(val);
+        }
+        let mut warmed: WarmedAverage<u16, u32, 3> = sut.into_warmed().expect("Window is full");
+        assert_eq!(20_u16, warmed.current_average());
+        assert_eq!(30_u16, warmed.average(40_u16));
+        assert_eq!(Some(30_u16), warmed.into_inner().current_average());
+    }
+
+    #[test]
+    fn given_pairs_in_window_when_block_averages_then_return_each_pair_average() {
+        let mut sut = MovingAverage::<u16, u32, 8, false>::new();
+        assert_eq!(None, sut.block_averages::<2, 4>());
+        for val in [1_u16, 3_u16, 10_u16, 20_u16, 5_u16, 5_u16, 0_u16, 9_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(
+            Some([2_u16, 15_u16, 5_u16, 4_u16]),
+            sut.block_averages::<2, 4>()
+        );
+        assert_eq!(Some([8_u16, 4_u16]), sut.block_averages::<4, 2>());
+    }
+
+    #[test]
+    fn given_known_window_when_is_outlier_then_flag_only_anomalous_sample() {
+        let mut sut = MovingAverage::<u16, u32, 5>::new();
+        assert!(!sut.is_outlier(1000_u16, 3_u32));
+        for val in [100_u16, 102_u16, 98_u16, 101_u16, 160_u16] {
+            let _ = sut.average(val);
+        }
+        assert!(!sut.is_outlier(104_u16, 3_u32));
+        assert!(sut.is_outlier(105_u16, 3_u32));
+        assert!(sut.is_outlier(90_u16, 3_u32));
+    }
+
+    #[test]
+    fn given_k_of_tcalc_max_when_is_outlier_then_flag_only_on_flat_window() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        let _ = sut.average(8_u16);
+        // Flat window, MAD 0, so the limit is zero however large k is.
+        assert!(sut.is_outlier(9_u16, u32::MAX));
+        let _ = sut.average(0_u16);
+        let _ = sut.average(4_u16);
+        // Median 4, MAD 4 from the deviations [4, 4, 0].
+        assert!(!sut.is_outlier(u16::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn given_empty_partial_and_full_filters_when_len_then_report_occupancy() {
+        let mut sut = MovingAverage::<u16, u32, 3, false>::new();
+        assert_eq!((0, true, 3), (sut.len(), sut.is_empty(), sut.capacity()));
+        let _ = sut.average(1_u16);
+        assert_eq!((1, false, 3), (sut.len(), sut.is_empty(), sut.capacity()));
+        for _ in 0..5_usize {
+            let _ = sut.average(1_u16);
+        }
+        assert_eq!((3, false, 3), (sut.len(), sut.is_empty(), sut.capacity()));
+        let mut prefilled = MovingAverage::<u16, u32, 3>::new();
+        let _ = prefilled.average(1_u16);
+        assert_eq!((1, 3), (prefilled.len(), prefilled.buffer().len()));
+    }
+
+    #[test]
+    fn given_present_value_when_retract_then_shift_window_and_pad_oldest() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Ok(20_u16), sut.retract(30_u16));
+        assert_eq!(
+            [10_u16, 10_u16, 20_u16, 40_u16],
+            *sut.buffer()
+                .oldest_ordered()
+                .copied()
+                .collect::<Vec<u16, 4>>()
+        );
+        assert_eq!(30_u16, sut.average(50_u16));
+    }
+
+    #[test]
+    fn given_no_prefill_when_retract_then_window_shrinks() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        for val in [10_u16, 20_u16, 60_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Ok(35_u16), sut.retract(20_u16));
+        assert_eq!(2, sut.len());
+        assert_eq!(Ok(60_u16), sut.retract(10_u16));
+        assert_eq!(Ok(60_u16), sut.retract(60_u16));
+        assert_eq!(None, sut.current_average());
+    }
+
+    #[test]
+    fn given_absent_value_when_retract_then_return_not_in_window() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(Err(MovingAverageError::NotInWindow), sut.retract(5_u16));
+        let _ = sut.average(10_u16);
+        assert_eq!(Err(MovingAverageError::NotInWindow), sut.retract(5_u16));
+        assert_eq!(Some(10_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_u8_in_u32_when_check_squares_capacity_then_accept_up_to_two_pow_sixteen() {
+        assert_eq!(
+            Ok(()),
+            MovingAverage::<u8, u32, 0x1_0000>::check_squares_capacity()
+        );
+        assert_eq!(
+            Err(MovingAverageError::SquaresOverflow),
+            MovingAverage::<u8, u32, 0x1_0001>::check_squares_capacity()
+        );
+        assert_eq!(
+            Ok(()),
+            MovingAverage::<u8, u32, 3>::new().validate_variance_depth()
+        );
+    }
+
+    #[test]
+    fn given_u32_in_wider_types_when_check_squares_capacity_then_need_double_width() {
+        assert_eq!(
+            Err(MovingAverageError::SquaresOverflow),
+            MovingAverage::<u32, u64, 2>::check_squares_capacity()
+        );
+        assert_eq!(
+            Ok(()),
+            MovingAverage::<u32, u128, 4>::check_squares_capacity()
+        );
+    }
+
+    #[test]
+    fn given_signed_tcalc_when_check_squares_capacity_then_only_positive_range_counts() {
+        assert_eq!(
+            Ok(()),
+            MovingAverage::<u8, i32, 0x8000>::check_squares_capacity()
+        );
+        assert_eq!(
+            Err(MovingAverageError::SquaresOverflow),
+            MovingAverage::<u8, i32, 0x8001>::check_squares_capacity()
+        );
+    }
+
+    #[test]
+    fn given_widest_types_when_new_then_capacity_check_does_not_overflow() {
+        let mut sut = MovingAverage::<u64, u128, 4>::new();
+        assert_eq!(u64::MAX, sut.average(u64::MAX));
+        assert_eq!(Ok(()), sut.validate_depth());
+        assert_eq!(
+            Err(MovingAverageError::SquaresOverflow),
+            sut.validate_variance_depth()
+        );
+    }
+
+    #[test]
+    fn given_skewed_window_when_mean_median_gap_then_return_signed_gap() {
+        let mut sut = MovingAverage::<i16, i32, 5>::new();
+        assert_eq!(None, sut.mean_median_gap());
+        for val in [10_i16, 11_i16, 12_i16, 13_i16, 104_i16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(18_i16), sut.mean_median_gap());
+        for val in [-80_i16, 10_i16, 11_i16, 12_i16, 13_i16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(-17_i16), sut.mean_median_gap());
+    }
+
+    #[test]
+    fn given_even_window_when_mean_median_gap_then_use_lower_middle_median() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [1_u16, 2_u16, 10_u16, 11_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(2_u16), sut.median());
+        assert_eq!(Some(6_u16), sut.current_average());
+        assert_eq!(Some(4_u16), sut.mean_median_gap());
+        // Median 2, MAD 1 from the deviations [0, 1, 8, 9].
+        assert!(!sut.is_outlier(5_u16, 3_u32));
+        assert!(sut.is_outlier(6_u16, 3_u32));
+    }
+
+    #[test]
+    fn given_left_skew_with_unsigned_t_when_mean_median_gap_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        for val in [0_u16, 50_u16, 51_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(None, sut.mean_median_gap());
+    }
+
+    #[test]
+    fn given_simple_average_when_coefficients_then_all_equal_and_sum_to_divisor() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let _ = sut.average(7_u16);
+        assert_eq!([1_u32; 4], sut.coefficients());
+        let mut partial = MovingAverage::<u16, u32, 4, false>::new();
+        let _ = partial.average(7_u16);
+        let _ = partial.average(9_u16);
+        assert_eq!([1_u32, 1_u32, 0_u32, 0_u32], partial.coefficients());
+    }
+
+    #[test]
+    fn given_n_real_samples_when_average_status_then_warming_becomes_valid() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        assert_eq!((10_u16, Quality::Warming), sut.average_status(10_u16));
+        assert_eq!((13_u16, Quality::Warming), sut.average_status(20_u16));
+        assert_eq!((20_u16, Quality::Valid), sut.average_status(30_u16));
+        sut.fill(5_u16);
+        assert_eq!((5_u16, Quality::Warming), sut.average_status(5_u16));
+    }
+
+    #[test]
+    fn given_two_level_window_when_is_bimodal_then_return_true() {
+        let mut sut = MovingAverage::<u16, u32, 8>::new();
+        assert!(!sut.is_bimodal(10_u16));
+        for val in [
+            10_u16, 90_u16, 11_u16, 91_u16, 12_u16, 89_u16, 10_u16, 90_u16,
+        ] {
+            let _ = sut.average(val);
+        }
+        assert!(sut.is_bimodal(10_u16));
+        assert!(!sut.is_bimodal(80_u16));
+    }
+
+    #[test]
+    fn given_unimodal_or_lopsided_window_when_is_bimodal_then_return_false() {
+        let mut sut = MovingAverage::<u16, u32, 8>::new();
+        for val in [
+            10_u16, 11_u16, 12_u16, 13_u16, 11_u16, 12_u16, 10_u16, 13_u16,
+        ] {
+            let _ = sut.average(val);
+        }
+        assert!(!sut.is_bimodal(2_u16));
+        for val in [
+            10_u16, 11_u16, 12_u16, 13_u16, 11_u16, 12_u16, 10_u16, 90_u16,
+        ] {
+            let _ = sut.average(val);
+        }
+        assert!(!sut.is_bimodal(10_u16));
+        for val in [
+            10_u16, 50_u16, 90_u16, 10_u16, 50_u16, 90_u16, 10_u16, 90_u16,
+        ] {
+            let _ = sut.average(val);
+        }
+        assert!(!sut.is_bimodal(10_u16));
+    }
+
+    #[test]
+    fn given_depths_when_cutoff_fraction_then_return_reduced_approximation() {
+        assert_eq!(
+            (443, 4000),
+            MovingAverage::<u16, u32, 4>::new().cutoff_fraction()
+        );
+        assert_eq!(
+            (443, 16_000),
+            MovingAverage::<u16, u32, 16>::new().cutoff_fraction()
+        );
+        assert_eq!(
+            (1, 1000),
+            MovingAverage::<u16, u32, 443>::new().cutoff_fraction()
+        );
+    }
+
+    #[test]
+    fn given_depth_of_eight_when_cutoff_fraction_then_gain_is_near_half_power() {
+        let (num, den) = MovingAverage::<u16, u32, 8>::new().cutoff_fraction();
+        let f = f64::from(num) / f64::from(den);
+        let gain = (PI * f * 8.0_f64).sin() / (8.0_f64 * (PI * f).sin());
+        assert!((gain - FRAC_1_SQRT_2).abs() < 0.01_f64);
+    }
+
+    #[test]
+    fn given_glitched_newest_when_replace_newest_then_only_newest_changes() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16, 900_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Ok(35_u16), sut.replace_newest(50_u16));
+        assert_eq!(
+            [20_u16, 30_u16, 40_u16, 50_u16],
+            *sut.buffer()
+                .oldest_ordered()
+                .copied()
+                .collect::<Vec<u16, 4>>()
+        );
+        assert_eq!(45_u16, sut.average(60_u16));
+    }
+
+    #[test]
+    fn given_no_samples_when_replace_newest_then_return_empty_window() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        assert_eq!(
+            Err(MovingAverageError::EmptyWindow),
+            sut.replace_newest(5_u16)
+        );
+        let _ = sut.average(8_u16);
+        assert_eq!(Ok(5_u16), sut.replace_newest(5_u16));
+        assert_eq!(1, sut.len());
+    }
+
+    #[test]
+    fn given_step_when_average_anti_recency_then_lag_exceeds_weighted_average() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.average_anti_recency());
+        let _ = sut.average(0_u16);
+        let _ = sut.average(100_u16);
+        let _ = sut.average(100_u16);
+        let recency_weighted = sut
+            .correlate(&[1_u32, 2_u32, 3_u32, 4_u32])
+            .map(|total| total / 10_u32);
+        assert_eq!(Some(70_u32), recency_weighted);
+        assert_eq!(Some(50_u16), sut.current_average());
+        assert_eq!(Some(30_u16), sut.average_anti_recency());
+    }
+
+    #[test]
+    fn given_partial_window_when_average_anti_recency_then_weight_filled_samples() {
+        let mut sut = MovingAverage::<u16, u32, 8, false>::new();
+        let _ = sut.average(10_u16);
+        assert_eq!(Some(10_u16), sut.average_anti_recency());
+        let _ = sut.average(40_u16);
+        assert_eq!(Some(20_u16), sut.average_anti_recency());
+    }
+
+    #[test]
+    fn given_exact_mode_when_sum_reaches_accumulator_limit_then_average_is_exact() {
+        let mut sut = MovingAverage::<u8, u16, 256, true, false>::new_preloaded(0_u8);
+        for _ in 0..256_usize {
+            let _ = sut.average(0xFF_u8);
+        }
+        assert_eq!(Some(0xFF_u8), sut.current_average());
+        assert_eq!(254_u8, sut.average(0_u8));
+    }
+
+    #[test]
+    fn given_saturating_mode_when_sum_exceeds_accumulator_then_sum_clamps() {
+        let mut sut = MovingAverage::<u8, u16, 512, true, true>::new();
+        assert_eq!(127_u8, sut.average(0xFF_u8));
+        assert_eq!(127_u8, sut.average(0xFF_u8));
+        assert_eq!(127_u8, sut.average(0_u8));
+        let mut low = MovingAverage::<u8, u16, 512, true, true>::new();
+        assert_eq!(0_u8, low.average(0_u8));
+        assert_eq!(0_u8, low.average(0xFF_u8));
+    }
+
+    #[test]
+    fn given_known_window_when_diagnostics_then_each_field_matches() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.diagnostics());
+        for val in [2_u16, 4_u16, 4_u16, 6_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(
+            Some(Diagnostics {
+                average: 4_u16,
+                sum: 16_u32,
+                count: 4,
+                min: 2_u16,
+                max: 6_u16,
+                variance: 2_u32,
+            }),
+            sut.diagnostics()
+        );
+    }
+
+    #[test]
+    fn given_output_scale_when_diagnostics_then_min_and_max_are_unscaled_samples() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_with_output_scale(10_u32, 1_u32);
+        for _ in 0..4_usize {
+            let _ = sut.average(100_u16);
+        }
+        assert_eq!(
+            Some(Diagnostics {
+                average: 1000_u16,
+                sum: 400_u32,
+                count: 4,
+                min: 100_u16,
+                max: 100_u16,
+                variance: 0_u32,
+            }),
+            sut.diagnostics()
+        );
+    }
+
+    #[test]
+    fn given_partial_window_when_diagnostics_then_count_real_samples() {
+        let mut sut = MovingAverage::<i16, i32, 8, false>::new();
+        let _ = sut.average(-10_i16);
+        let _ = sut.average(10_i16);
+        let diagnostics = sut.diagnostics().expect("Window has samples");
+        assert_eq!(
+            (2, -10_i16, 10_i16),
+            (diagnostics.count, diagnostics.min, diagnostics.max)
+        );
+        assert_eq!(
+            (0_i16, 0_i32, 100_i32),
+            (diagnostics.average, diagnostics.sum, diagnostics.variance)
+        );
+    }
+
+    #[test]
+    fn given_prefilled_window_when_len_then_count_only_real_samples() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        assert!(sut.is_empty());
+        for expected in 1..=3_usize {
+            assert!(!sut.is_full());
+            let _ = sut.average(7_u16);
+            assert_eq!((expected, 3), (sut.len(), sut.buffer().len()));
+        }
+        assert!(sut.is_full());
+        let _ = sut.average(7_u16);
+        assert_eq!(3, sut.len());
+        sut.fill(9_u16);
+        assert!(sut.is_empty());
+    }
+
+    #[test]
+    fn given_oversized_depth_when_try_new_then_return_capacity_overflow() {
+        assert_eq!(
+            Some(MovingAverageError::CapacityOverflow),
+            MovingAverage::<u8, u16, 512>::try_new().err()
+        );
+        let mut sut = MovingAverage::<u8, u16, 256>::try_new().expect("Depth should fit");
+        assert_eq!(0xFF_u8, sut.average(0xFF_u8));
+        assert!(MovingAverage::<u8, u16, 512, true, true>::try_new().is_ok());
+    }
+
+    #[test]
+    fn given_12_bit_packed_stream_when_average_packed_then_match_fed_samples() {
+        let packed = [0x23_u8, 0x61_u8, 0x45_u8, 0x89_u8, 0xC7_u8, 0xAB_u8];
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        let mut reference = MovingAverage::<u16, u32, 4, false>::new();
+        for val in [0x123_u16, 0x456_u16, 0x789_u16, 0xABC_u16] {
+            let _ = reference.average(val);
+        }
+        assert_eq!(
+            reference.current_average(),
+            sut.average_packed(&packed, 12_u8)
+        );
+        assert_eq!(reference.buffer().as_slice(), sut.buffer().as_slice());
+    }
+
+    #[test]
+    fn given_10_bit_packed_stream_when_average_packed_then_ignore_trailing_bits() {
+        // 0x3FF, 0x001, 0x200 in 30 bits, with 2 padding bits.
+        let packed = [0xFF_u8, 0x07_u8, 0x00_u8, 0x20_u8];
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        assert_eq!(Some(0x200_u16), sut.average_packed(&packed, 10_u8));
+        assert_eq!(3, sut.len());
+        assert_eq!(
+            Some(0x180_u16),
+            sut.average_packed(&[0x00_u8, 0x00_u8], 16_u8)
+        );
+    }
+
+    #[test]
+    fn given_unsupported_or_too_wide_packing_when_average_packed_then_feed_nothing() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new();
+        assert_eq!(None, sut.average_packed(&[0xFF_u8; 3], 12_u8));
+        assert_eq!(None, sut.average_packed(&[0xFF_u8; 3], 4_u8));
+        assert!(sut.is_empty());
+        assert_eq!(Some(0xFF_u8), sut.average_packed(&[0xFF_u8; 3], 8_u8));
+    }
+
+    #[test]
+    fn given_scaled_output_outside_t_when_average_saturating_then_clamp_to_t() {
+        let mut sut = MovingAverage::<u8, u16, 2>::new_with_output_scale(4_u16, 1_u16);
+        assert_eq!(40_u8, sut.average_saturating(10_u8));
+        assert_eq!(0xFF_u8, sut.average_saturating(0xFF_u8));
+        let mut signed = MovingAverage::<i8, i16, 2>::new_with_output_scale(4_i16, 1_i16);
+        assert_eq!(i8::MIN, signed.average_saturating(-100_i8));
+        assert_eq!(0_i8, signed.average_saturating(100_i8));
+        assert_eq!(i8::MAX, signed.average_saturating(100_i8));
+        assert_eq!(i8::MAX, signed.average_saturating(100_i8));
+        assert_eq!(1, signed.samples_since_change());
+    }
+
+    #[test]
+    fn given_in_range_inputs_when_average_saturating_then_match_average() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new();
+        let mut reference = MovingAverage::<i16, i32, 4>::new();
+        for val in [i16::MIN, i16::MAX, -7_i16, 300_i16, i16::MIN, i16::MIN] {
+            assert_eq!(reference.average(val), sut.average_saturating(val));
+        }
+        assert_eq!(reference.current_average(), sut.current_average());
+    }
+
+    #[test]
+    fn given_full_window_when_average_strided_window_then_average_selected_phase() {
+        let mut sut = MovingAverage::<u16, u32, 6, false>::new();
+        for val in [10_u16, 100_u16, 20_u16, 200_u16, 30_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(None, sut.average_strided_window(0, 2));
+        let _ = sut.average(300_u16);
+        assert_eq!(Some(20_u16), sut.average_strided_window(0, 2));
+        assert_eq!(Some(200_u16), sut.average_strided_window(1, 2));
+        assert_eq!(Some(160_u16), sut.average_strided_window(2, 3));
+        assert_eq!(Some(300_u16), sut.average_strided_window(5, 4));
+    }
+
+    #[test]
+    fn given_empty_selection_when_average_strided_window_then_return_none() {
+        let sut = MovingAverage::<u16, u32, 4>::new_preloaded(5_u16);
+        assert_eq!(None, sut.average_strided_window(0, 0));
+        assert_eq!(None, sut.average_strided_window(4, 1));
+        assert_eq!(Some(5_u16), sut.average_strided_window(3, 1));
+    }
+
+    #[test]
+    fn given_even_and_odd_sums_when_average_rounded_then_round_half_up() {
+        let mut sut = MovingAverage::<u16, u32, 2>::new();
+        assert_eq!(3_u16, sut.average_rounded(3_u16));
+        assert_eq!(4_u16, sut.average_rounded(4_u16));
+        assert_eq!(4_u16, sut.average_rounded(4_u16));
+        let mut thirds = MovingAverage::<u16, u32, 3, false>::new();
+        let _ = thirds.average_rounded(1_u16);
+        assert_eq!(1_u16, thirds.average_rounded(1_u16));
+        assert_eq!(1_u16, thirds.average_rounded(2_u16));
+        assert_eq!(2_u16, thirds.average_rounded(2_u16));
+    }
+
+    #[test]
+    fn given_signed_negatives_when_average_rounded_then_round_half_away_from_zero() {
+        let mut sut = MovingAverage::<i16, i32, 2>::new();
+        let _ = sut.average_rounded(-3_i16);
+        assert_eq!(-4_i16, sut.average_rounded(-4_i16));
+        assert_eq!(-3_i16, sut.current_average().expect("Window has samples"));
+        let mut thirds = MovingAverage::<i16, i32, 3>::new_preloaded(0_i16);
+        assert_eq!(0_i16, thirds.average_rounded(-1_i16));
+        assert_eq!(-1_i16, thirds.average_rounded(-1_i16));
+        assert_eq!(-2_i16, thirds.average_rounded(-3_i16));
+    }
+
+    #[test]
+    fn given_ramp_and_scale_when_average_rounded_then_share_the_output_stage() {
+        let mut sut = MovingAverage::<u16, u32, 2>::new_with_output_scale(3_u32, 2_u32);
+        let _ = sut.average_rounded(3_u16);
+        sut.transition_to(100_u16, 2);
+        // The ramp takes precedence: halfway from 4, the scaled 3, to 100.
+        assert_eq!(52_u16, sut.average_rounded(4_u16));
+        assert_eq!(100_u16, sut.average_rounded(4_u16));
+        // Window [4, 3] scaled by 3/2 is 5.25, and [3, 4] again.
+        assert_eq!(5_u16, sut.average_rounded(3_u16));
+        assert_eq!(0, sut.samples_since_change());
+        assert_eq!(5_u16, sut.average_rounded(4_u16));
+        assert_eq!(1, sut.samples_since_change());
+        // Window [4, 4] scaled is 6, and [4, 5] is 6.75, rounded to 7.
+        assert_eq!(6_u16, sut.average_rounded(4_u16));
+        assert_eq!(7_u16, sut.average_rounded(5_u16));
+        assert_eq!(Some(6_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_extreme_samples_when_average_rounded_then_do_not_overflow() {
+        let mut sut = MovingAverage::<u8, u16, 256>::new();
+        assert_eq!(0xFF_u8, sut.average_rounded(0xFF_u8));
+        assert_eq!(0xFF_u8, sut.average_rounded(0xFE_u8));
+        let mut signed = MovingAverage::<i8, i16, 2>::new();
+        let _ = signed.average_rounded(i8::MIN);
+        assert_eq!(i8::MIN, signed.average_rounded(-127_i8));
+        assert_eq!(
+            i8::MAX,
+            MovingAverage::<i8, i16, 2>::new().average_rounded(i8::MAX)
+        );
+    }
+
+    const SPREAD: [u16; 8] = [2_u16, 4_u16, 4_u16, 4_u16, 5_u16, 5_u16, 7_u16, 9_u16];
+
+    #[test]
+    fn given_samples_at_known_deviations_when_zscore_then_return_scaled_score() {
+        let mut sut = MovingAverage::<i16, i32, 8>::new();
+        for val in SPREAD {
+            let _ = sut.average(val as i16);
+        }
+        assert_eq!(Some(2000_i32), sut.zscore(9_i16));
+        assert_eq!(Some(-2000_i32), sut.zscore(1_i16));
+        assert_eq!(Some(500_i32), sut.zscore(6_i16));
+        assert_eq!(Some(0_i32), sut.zscore(5_i16));
+        assert_eq!(8, sut.len());
+    }
+
+    #[test]
+    fn given_unsigned_tcalc_when_zscore_below_mean_then_return_magnitude() {
+        let mut sut = MovingAverage::<u16, u32, 8, false>::new();
+        for val in SPREAD {
+            assert_eq!(None, sut.zscore(val));
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(2000_u32), sut.zscore(1_u16));
+    }
+
+    #[test]
+    fn given_constant_window_when_zscore_then_return_none() {
+        let sut = MovingAverage::<u16, u32, 4>::new_preloaded(7_u16);
+        assert_eq!(None, sut.zscore(8_u16));
+    }
+
+    #[test]
+    fn given_cloned_filter_when_clone_mutated_then_original_is_unaffected() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        let mut hypothetical = sut.clone();
+        for _ in 0..4_usize {
+            let _ = hypothetical.average(1000_u16);
+        }
+        assert_eq!(Some(1000_u16), hypothetical.current_average());
+        assert_eq!(Some(25_u16), sut.current_average());
+        assert_eq!(35_u16, sut.average(50_u16));
+    }
+
+    #[test]
+    fn given_transition_when_average_then_output_ramps_linearly_to_target() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_preloaded(100_u16);
+        sut.transition_to(200_u16, 4);
+        for expected in [125_u16, 150_u16, 175_u16, 200_u16] {
+            assert_eq!(expected, sut.average(100_u16));
+        }
+        assert_eq!(100_u16, sut.average(100_u16));
+    }
+
+    #[test]
+    fn given_downward_transition_when_average_then_ignore_inputs_until_done() {
+        let mut sut = MovingAverage::<i16, i32, 2>::new_preloaded(0_i16);
+        sut.transition_to(-30_i16, 3);
+        assert_eq!(-10_i16, sut.average(500_i16));
+        assert_eq!(-20_i16, sut.average(500_i16));
+        sut.transition_to(0_i16, 0);
+        assert_eq!(500_i16, sut.average(500_i16));
+    }
+
+    #[test]
+    fn given_marked_sample_when_window_slides_then_count_down_to_eviction() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_preloaded(0_u16);
+        assert_eq!(None, sut.samples_until_eviction(999_u16));
+        let _ = sut.average(999_u16);
+        for expected in [3_usize, 2_usize, 1_usize, 0_usize] {
+            assert_eq!(Some(expected), sut.samples_until_eviction(999_u16));
+            let _ = sut.average(1_u16);
+        }
+        assert_eq!(None, sut.samples_until_eviction(999_u16));
+    }
+
+    #[test]
+    fn given_filling_window_when_samples_until_eviction_then_include_fill_calls() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        let _ = sut.average(999_u16);
+        let _ = sut.average(999_u16);
+        assert_eq!(Some(2_usize), sut.samples_until_eviction(999_u16));
+        let _ = sut.average(1_u16);
+        let _ = sut.average(1_u16);
+        assert_eq!(Some(2_usize), sut.samples_until_eviction(1_u16));
+    }
+
+    #[test]
+    fn given_missing_sample_when_tick_missing_then_hold_or_extrapolate() {
+        let mut hold = MovingAverage::<u16, u32, 4, false>::new();
+        assert_eq!(None, hold.tick_missing());
+        let _ = hold.average(10_u16);
+        let _ = hold.average(20_u16);
+        let mut extrapolate = hold.clone();
+        extrapolate.set_missing_policy(MissingPolicy::Extrapolate);
+        assert_eq!(Some(16_u16), hold.tick_missing());
+        assert_eq!(Some(20_u16), extrapolate.tick_missing());
+        assert_eq!(Some(&20_u16), hold.buffer().recent());
+        assert_eq!(Some(&30_u16), extrapolate.buffer().recent());
+        assert_eq!(3, extrapolate.len());
+    }
+
+    #[test]
+    fn given_falling_samples_when_tick_missing_extrapolates_then_saturate_at_t_min() {
+        let mut sut = MovingAverage::<u8, u16, 2, false>::new();
+        sut.set_missing_policy(MissingPolicy::Extrapolate);
+        let _ = sut.average(0xFF_u8);
+        assert_eq!(Some(0xFF_u8), sut.tick_missing());
+        let _ = sut.average(100_u8);
+        assert_eq!(Some(50_u8), sut.tick_missing());
+        assert_eq!(Some(0_u8), sut.tick_missing());
+        let _ = sut.average(0xF0_u8);
+        assert_eq!(Some(0xF7_u8), sut.tick_missing());
+        assert_eq!(Some(&0xFF_u8), sut.buffer().recent());
+    }
+
+    #[test]
+    fn given_window_when_min_then_return_smallest_real_sample() {
+        let mut sut = MovingAverage::<i16, i32, 3>::new();
+        assert_eq!(None, sut.min());
+        for (val, expected) in [
+            (5_i16, 5_i16),
+            (-2_i16, -2_i16),
+            (9_i16, -2_i16),
+            (7_i16, -2_i16),
+            (8_i16, 7_i16),
+        ] {
+            let _ = sut.average(val);
+            assert_eq!(Some(expected), sut.min());
+        }
+    }
+
+    #[test]
+    fn given_preloaded_fill_when_min_then_ignore_fill_copies() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_preloaded(0_u16);
+        assert_eq!(None, sut.min());
+        let _ = sut.average(40_u16);
+        let _ = sut.average(30_u16);
+        assert_eq!(Some(30_u16), sut.min());
+        assert_eq!(Some(17_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_spike_when_max_then_report_until_evicted() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        assert_eq!((None, None), (sut.max(), sut.min_max()));
+        for (val, expected) in [
+            (5_u16, 5_u16),
+            (90_u16, 90_u16),
+            (7_u16, 90_u16),
+            (6_u16, 90_u16),
+            (8_u16, 8_u16),
+        ] {
+            let _ = sut.average(val);
+            assert_eq!(Some(expected), sut.max());
+        }
+        assert_eq!(Some((6_u16, 8_u16)), sut.min_max());
+    }
+
+    #[test]
+    fn given_preloaded_fill_when_min_max_then_ignore_fill_copies() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new_preloaded(1000_i16);
+        assert_eq!(None, sut.min_max());
+        let _ = sut.average(-4_i16);
+        assert_eq!(Some((-4_i16, -4_i16)), sut.min_max());
+        let _ = sut.average(3_i16);
+        assert_eq!(Some((-4_i16, 3_i16)), sut.min_max());
+        assert_eq!((Some(-4_i16), Some(3_i16)), (sut.min(), sut.max()));
+    }
+
+    #[test]
+    fn given_known_datasets_when_variance_then_return_population_variance() {
+        let mut sut = MovingAverage::<u16, u32, 8>::new();
+        assert_eq!(None, sut.variance());
+        for val in SPREAD {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(4_u32), sut.variance());
+        let mut signed = MovingAverage::<i16, i64, 4>::new();
+        for val in [i16::MIN, i16::MAX, i16::MIN, i16::MAX] {
+            let _ = signed.average(val);
+        }
+        assert_eq!(Ok(()), signed.validate_variance_depth());
+        assert_eq!(Some(0x3FFF_8000_i64), signed.variance());
+    }
+
+    #[test]
+    fn given_partial_window_when_variance_then_use_samples_seen() {
+        let mut sut = MovingAverage::<u8, u32, 8, false>::new();
+        let _ = sut.average(1_u8);
+        assert_eq!(Some(0_u32), sut.variance());
+        let _ = sut.average(5_u8);
+        assert_eq!(Some(4_u32), sut.variance());
+    }
+
+    #[test]
+    fn given_seeded_filter_when_average_then_match_fed_reference() {
+        let readings = [3_u16, 9_u16, 27_u16, 81_u16, 243_u16, 729_u16];
+        let mut sut = MovingAverage::<u16, u32, 4>::from_slice(&readings);
+        let mut reference = MovingAverage::<u16, u32, 4>::new();
+        for val in readings {
+            let _ = reference.average(val);
+        }
+        assert!(
+            reference
+                .buffer()
+                .oldest_ordered()
+                .eq(sut.buffer().oldest_ordered())
+        );
+        assert_eq!(reference.average(5_u16), sut.average(5_u16));
+        assert!(sut.is_full());
+    }
+
+    #[test]
+    fn given_short_slice_when_from_slice_then_pad_or_stay_partial() {
+        let mut prefilled = MovingAverage::<i16, i32, 4>::from_slice(&[-8_i16, 8_i16]);
+        assert_eq!(
+            (Some(-4_i16), 2),
+            (prefilled.current_average(), prefilled.len())
+        );
+        assert_eq!(0_i16, prefilled.average(8_i16));
+        let partial = MovingAverage::<i16, i32, 4, false>::from_slice(&[-8_i16, 8_i16]);
+        assert_eq!(
+            (Some(0_i16), 2),
+            (partial.current_average(), partial.buffer().len())
+        );
+        assert!(MovingAverage::<i16, i32, 4>::from_slice(&[]).is_empty());
+    }
+
+    #[test]
+    fn given_burst_when_extend_then_match_repeated_average() {
+        let burst = [4_i16, -90_i16, 17_i16, 3_i16, 250_i16, -1_i16, 0_i16];
+        let mut sut = MovingAverage::<i16, i32, 4>::new();
+        let mut reference = MovingAverage::<i16, i32, 4>::new();
+        let mut expected = None;
+        for val in burst {
+            expected = Some(reference.average(val));
+        }
+        assert_eq!(expected, sut.extend(burst));
+        assert_eq!(expected, sut.extend([0_i16; 0]));
+        assert_eq!(reference.average(9_i16), sut.average(9_i16));
+    }
+
+    #[test]
+    fn given_new_filter_when_extend_empty_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.extend([0_u16; 0]));
+        assert_eq!(
+            Some(5_u16),
+            sut.extend((1_u16..=3_u16).map(|val| val * 3_u16))
+        );
+    }
+
+    #[test]
+    fn given_wrapped_window_when_iter_then_yield_oldest_first() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        assert_eq!(None, sut.iter().next());
+        let _ = sut.average(1_u16);
+        assert!(sut.iter().eq([1_u16, 1_u16, 1_u16].iter()));
+        for val in [2_u16, 3_u16, 4_u16, 5_u16] {
+            let _ = sut.average(val);
+        }
+        assert!(sut.iter().eq([3_u16, 4_u16, 5_u16].iter()));
+    }
+
+    #[test]
+    fn given_filling_window_when_average_partial_then_mean_of_real_samples() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        let mut biased = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(100_u16, sut.average_partial(100_u16));
+        assert_eq!(150_u16, sut.average_partial(200_u16));
+        let _ = biased.average(100_u16);
+        assert_eq!(125_u16, biased.average(200_u16));
+        assert_eq!(200_u16, sut.average_partial(300_u16));
+        assert_eq!(250_u16, sut.average_partial(400_u16));
+        assert_eq!(350_u16, sut.average_partial(500_u16));
+    }
+
+    #[test]
+    fn given_preloaded_seed_when_average_partial_then_ignore_seed() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new_preloaded(1000_i16);
+        assert_eq!(-10_i16, sut.average_partial(-10_i16));
+        assert_eq!(-5_i16, sut.average_partial(0_i16));
+        let mut scaled = MovingAverage::<u16, u32, 4>::new_with_output_scale(2_u32, 1_u32);
+        let _ = scaled.average_partial(10_u16);
+        assert_eq!(30_u16, scaled.average_partial(20_u16));
+    }
+
+    #[test]
+    fn given_wrapped_ring_when_oldest_and_newest_then_return_chronological_ends() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!((None, None), (sut.oldest(), sut.newest()));
+        for val in 1_u16..=6_u16 {
+            let _ = sut.average(val);
+        }
+        assert_eq!((Some(3_u16), Some(6_u16)), (sut.oldest(), sut.newest()));
+        assert_eq!(Some(&3_u16), sut.iter().next());
+    }
+
+    #[test]
+    fn given_preloaded_seed_when_oldest_and_newest_then_skip_seed() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new_preloaded(50_i16);
+        assert_eq!((None, None), (sut.oldest(), sut.newest()));
+        let _ = sut.average(-7_i16);
+        let _ = sut.average(8_i16);
+        assert_eq!((Some(-7_i16), Some(8_i16)), (sut.oldest(), sut.newest()));
+    }
+
+    #[test]
+    fn given_repeating_cycle_when_ring_wraps_then_average_does_not_drift() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [100_u16, 200_u16, 300_u16, 400_u16] {
+            let _ = sut.average(val);
+        }
+        for _ in 0..16_usize {
+            for val in [100_u16, 200_u16, 300_u16, 400_u16] {
+                assert_eq!(250_u16, sut.average(val));
+            }
+        }
+        assert_eq!(Some(1000_u32), sut.current_sum());
+    }
+
+    #[cfg(feature = "unchecked-capacity")]
+    #[test]
+    fn given_unchecked_capacity_when_samples_are_bounded_then_average() {
+        let mut sut = MovingAverage::<u8, u16, 512>::new();
+        assert_eq!(
+            Err(MovingAverageError::CapacityOverflow),
+            sut.validate_depth()
+        );
+        for _ in 0..1024_usize {
+            assert_eq!(100_u8, sut.average(100_u8));
+        }
+    }
+
+    #[test]
+    fn given_initial_value_when_average_then_slide_from_seed_to_data() {
+        let mut sut = MovingAverage::<u16, u32, 4>::with_initial(200_u16);
+        assert_eq!(Some(200_u16), sut.current_average());
+        assert_eq!(Some(800_u32), sut.current_sum());
+        for expected in [225_u16, 250_u16, 275_u16, 300_u16, 300_u16] {
+            assert_eq!(expected, sut.average(300_u16));
+        }
+    }
+
+    #[test]
+    fn given_same_samples_when_compared_then_filters_are_equal() {
+        let mut sut = MovingAverage::<i16, i32, 4>::new();
+        let mut other = MovingAverage::<i16, i32, 4>::new();
+        assert!(sut == other);
+        for val in [3_i16, -9_i16, 27_i16, 4_i16, -1_i16, 12_i16] {
+            let _ = sut.average(val);
+            let _ = other.average(val);
+        }
+        assert!(sut == other);
+        let _ = other.average(5_i16);
+        assert!(sut != other);
+    }
+
+    #[test]
+    fn given_different_ring_offsets_when_compared_then_filters_are_equal() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in 1_u16..=7_u16 {
+            let _ = sut.average(val);
+        }
+        let other = MovingAverage::<u16, u32, 4>::from_slice(&[4_u16, 5_u16, 6_u16, 7_u16]);
+        assert_ne!(sut.buffer().as_slice(), other.buffer().as_slice());
+        assert!(sut == other);
+    }
+
+    #[test]
+    fn given_wrapped_ring_when_debug_formatted_then_window_is_chronological() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        assert_eq!(
+            "MovingAverage { depth: 3, average: None, window: [] }",
+            format!("{sut:?}")
+        );
+        for val in [10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(
+            "MovingAverage { depth: 3, average: Some(30), window: [20, 30, 40] }",
+            format!("{sut:?}")
+        );
+    }
+
+    #[test]
+    fn given_negative_fraction_when_average_floored_then_round_towards_negative_infinity() {
+        let mut sut = MovingAverage::<i16, i32, 2>::new();
+        let mut truncated = MovingAverage::<i16, i32, 2>::new();
+        for val in [-1_i16, -2_i16] {
+            let _ = truncated.average(val);
+        }
+        assert_eq!(-1_i16, sut.average_floored(-1_i16));
+        assert_eq!(-2_i16, sut.average_floored(-2_i16));
+        assert_eq!(Some(-1_i16), truncated.current_average());
+        assert_eq!(-3_i16, sut.average_floored(-4_i16));
+        assert_eq!(-1_i16, sut.average_floored(3_i16));
+        assert_eq!(3_i16, sut.average_floored(4_i16));
+    }
+
+    #[test]
+    fn given_ramp_and_scale_when_average_floored_then_share_the_output_stage() {
+        let mut sut = MovingAverage::<i16, i32, 2>::new_with_output_scale(3_i32, 2_i32);
+        let _ = sut.average_floored(-3_i16);
+        sut.transition_to(-100_i16, 2);
+        // The ramp takes precedence: halfway from -4, the scaled -3, to -100.
+        assert_eq!(-52_i16, sut.average_floored(-4_i16));
+        assert_eq!(-100_i16, sut.average_floored(-4_i16));
+        // Window [-4, -3] scaled by 3/2 is -5.25, and [-3, -4] again.
+        assert_eq!(-6_i16, sut.average_floored(-3_i16));
+        assert_eq!(0, sut.samples_since_change());
+        assert_eq!(-6_i16, sut.average_floored(-4_i16));
+        assert_eq!(1, sut.samples_since_change());
+        assert_eq!(Some(-5_i16), sut.current_average());
+    }
+
+    #[test]
+    fn given_unsigned_tcalc_when_average_floored_then_match_average() {
+        let mut sut = MovingAverage::<u8, u16, 3>::new();
+        let mut truncated = MovingAverage::<u8, u16, 3>::new();
+        for val in [7_u8, 0_u8, 255_u8, 1_u8] {
+            assert_eq!(truncated.average(val), sut.average_floored(val));
+        }
+    }
+
+    #[test]
+    fn given_undersized_tcalc_when_average_checked_fill_overflows_then_none_and_unchanged() {
+        let mut sut = MovingAverage::<u8, u16, 512, true, true>::new();
+        assert_eq!(None, sut.average_checked(200_u8));
+        assert_eq!(None, sut.current_sum());
+        assert!(sut.is_empty());
+        assert_eq!(Some(100_u8), sut.average_checked(100_u8));
+        assert_eq!(Some(51_200_u16), sut.current_sum());
+    }
+
+    #[test]
+    fn given_undersized_tcalc_when_average_checked_sum_overflows_then_none_and_unchanged() {
+        let mut sut = MovingAverage::<u8, u16, 512, false, true>::new();
+        let mut last = None;
+        for _ in 0..0x101_usize {
+            last = sut.average_checked(255_u8);
+        }
+        assert_eq!(Some(255_u8), last);
+        assert_eq!(Some(0xFFFF_u16), sut.current_sum());
+        assert_eq!(None, sut.average_checked(1_u8));
+        assert_eq!(Some(0xFFFF_u16), sut.current_sum());
+        assert_eq!(0x101, sut.len());
+        assert_eq!(Some(254_u8), sut.average_checked(0_u8));
+    }
+
+    #[test]
+    fn given_scaled_output_outside_t_when_average_checked_then_none() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new_with_output_scale(2_u16, 1_u16);
+        assert_eq!(Some(200_u8), sut.average_checked(100_u8));
+        assert_eq!(None, sut.average_checked(255_u8));
+        assert_eq!(Some(400_u16), sut.current_sum());
+    }
+
+    #[test]
+    fn given_const_filter_when_average_then_match_new() {
+        const FILTER: MovingAverage<i16, i32, 4> = MovingAverage::new_const();
+        let mut sut = FILTER;
+        let mut other = MovingAverage::<i16, i32, 4>::new();
+        for val in [-40_i16, 8_i16, 100_i16, 3_i16, -7_i16] {
+            assert_eq!(other.average(val), sut.average(val));
+        }
+        assert_eq!(other, sut);
+        let mut wide = const { MovingAverage::<u8, i32, 0x1234>::new_const() };
+        assert_eq!(3_u8, wide.average(3_u8));
+        assert_eq!(Some(0x369C_i32), wide.current_sum());
+    }
+
+    fn settle<F: Filter<u16>>(filter: &mut F, samples: &[u16]) -> Option<u16> {
+        for &val in samples {
+            let _ = filter.update(val);
+        }
+        filter.value()
+    }
+
+    #[test]
+    fn given_either_filter_when_used_through_filter_trait_then_smooth_the_step() {
+        let step = [0_u16, 0_u16, 1000_u16, 1000_u16];
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.value());
+        assert_eq!(Some(500_u16), settle(&mut sut, &step));
+        let mut other = Ewma::<u16, u32>::new(1_u32, 2_u32);
+        assert_eq!(Some(750_u16), settle(&mut other, &step));
+    }
+
+    #[test]
+    fn given_full_window_when_average_with_evicted_then_return_oldest_prior_sample() {
+        let mut sut = MovingAverage::<i16, i32, 3>::new();
+        assert_eq!((-30_i16, None), sut.average_with_evicted(-30_i16));
+        assert_eq!((-15_i16, None), sut.average_with_evicted(15_i16));
+        assert_eq!((0_i16, None), sut.average_with_evicted(15_i16));
+        for (val, evicted) in [
+            (60_i16, -30_i16),
+            (3_i16, 15_i16),
+            (9_i16, 15_i16),
+            (0_i16, 60_i16),
+        ] {
+            let oldest = sut.oldest();
+            let (average, out) = sut.average_with_evicted(val);
+            assert_eq!((Some(evicted), oldest), (out, out));
+            assert_eq!(sut.current_average(), Some(average));
+        }
+    }
+
+    #[test]
+    fn given_seed_or_sentinel_when_average_with_evicted_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 2>::with_initial(500_u16);
+        assert_eq!((300_u16, None), sut.average_with_evicted(100_u16));
+        assert_eq!((150_u16, None), sut.average_with_evicted(200_u16));
+        let mut sentinel = MovingAverage::<u16, u32, 2>::new_with_sentinel(0xFFFF_u16);
+        let _ = sentinel.average(4_u16);
+        let _ = sentinel.average(8_u16);
+        assert_eq!((6_u16, None), sentinel.average_with_evicted(0xFFFF_u16));
+        assert_eq!((10_u16, Some(4_u16)), sentinel.average_with_evicted(12_u16));
+    }
+
+    #[test]
+    fn given_unsorted_window_with_outlier_when_median_then_return_middle_sample() {
+        let mut sut = MovingAverage::<i16, i32, 5, false>::new();
+        assert_eq!(None, sut.median());
+        for val in [7_i16, -3_i16, 7_i16, 900_i16, -20_i16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(7_i16), sut.median());
+        let _ = sut.average(-3_i16);
+        assert_eq!(Some(-3_i16), sut.median());
+    }
+
+    #[test]
+    fn given_even_length_when_median_then_return_lower_middle_sample() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        for val in [40_u16, 10_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(10_u16), sut.median());
+        for val in [30_u16, 20_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(20_u16), sut.median());
+    }
+
+    #[test]
+    fn given_n_minus_one_samples_when_is_warmed_then_false_until_nth() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [5_u16, 6_u16, 7_u16] {
+            let _ = sut.average(val);
+            assert!(!sut.is_warmed());
+        }
+        assert_eq!(4, sut.buffer().len());
+        let _ = sut.average(8_u16);
+        assert!(sut.is_warmed());
+    }
+
+    #[test]
+    fn given_wrapped_window_when_drain_then_yield_oldest_first_and_reset() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        for val in [1_u16, 2_u16, 3_u16, 4_u16, 5_u16] {
+            let _ = sut.average(val);
+        }
+        let drained = sut.drain();
+        assert_eq!((3, Some(3)), drained.size_hint());
+        assert!(drained.eq([3_u16, 4_u16, 5_u16]));
+        assert!(sut.is_empty());
+        assert_eq!(None, sut.current_sum());
+        assert_eq!(0, sut.buffer().len());
+        assert_eq!(MovingAverage::<u16, u32, 3>::new(), sut);
+        assert_eq!(9_u16, sut.average(9_u16));
+    }
+
+    #[test]
+    fn given_partly_read_drain_when_checked_then_filter_is_already_reset() {
+        let mut sut = MovingAverage::<i16, i32, 4, false>::new();
+        for val in [-8_i16, 6_i16] {
+            let _ = sut.average(val);
+        }
+        let mut drained = sut.drain();
+        assert_eq!(Some(-8_i16), drained.next());
+        assert_eq!((1, Some(1)), drained.size_hint());
+        assert!(sut.is_empty());
+        assert_eq!(None, sut.current_average());
+        assert_eq!(0_usize, sut.drain().count());
+    }
+
+    #[test]
+    fn given_shorter_active_window_when_average_then_track_newest_samples_only() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        sut.set_active_window(2);
+        assert_eq!(2, sut.active_window());
+        assert_eq!(4, sut.capacity());
+        assert_eq!(Some(35_u16), sut.current_average());
+        assert_eq!(45_u16, sut.average(50_u16));
+        assert!(sut.iter().copied().eq([40_u16, 50_u16]));
+        assert_eq!(2, sut.len());
+        assert!(sut.is_full());
+        assert_eq!(Some(40_u16), sut.min());
+    }
+
+    #[test]
+    fn given_shortened_window_when_lengthened_then_stored_samples_rejoin() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        sut.set_active_window(1);
+        assert_eq!(Some(40_u16), sut.current_average());
+        let _ = sut.average(50_u16);
+        sut.set_active_window(4);
+        assert_eq!(Some(140_u32), sut.current_sum());
+        assert_eq!(45_u16, sut.average(60_u16));
+    }
+
+    #[test]
+    fn given_shorter_active_window_without_prefill_when_average_then_divide_by_samples_seen() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        sut.set_active_window(2);
+        assert_eq!(10_u16, sut.average(10_u16));
+        assert_eq!(15_u16, sut.average(20_u16));
+        assert_eq!(25_u16, sut.average(30_u16));
+        assert_eq!(3, sut.buffer().len());
+        assert!(sut.iter().copied().eq([20_u16, 30_u16]));
+    }
+
+    #[test]
+    fn given_shorter_active_window_when_first_average_then_prefill_covers_it() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        sut.set_active_window(2);
+        assert_eq!(8_u16, sut.average(8_u16));
+        assert_eq!(Some(16_u32), sut.current_sum());
+        assert!(!sut.is_full());
+        assert_eq!(10_u16, sut.average(12_u16));
+        assert!(sut.is_full());
+    }
+
+    #[test]
+    fn given_shorter_active_window_when_retract_then_next_stored_sample_slides_in() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [10_u16, 20_u16, 30_u16, 40_u16] {
+            let _ = sut.average(val);
+        }
+        sut.set_active_window(2);
+        assert_eq!(Err(MovingAverageError::NotInWindow), sut.retract(20_u16));
+        assert_eq!(Ok(30_u16), sut.retract(30_u16));
+        assert!(sut.iter().copied().eq([20_u16, 40_u16]));
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "window length must be in 1..=N")]
+    fn confirm_active_window_of_zero_panics() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        sut.set_active_window(0);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "window length must be in 1..=N")]
+    fn confirm_active_window_beyond_n_panics() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        sut.set_active_window(5);
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_bad_settings_when_try_setters_then_return_error_and_keep_state() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new();
+        for val in [10_u8, 20_u8, 30_u8, 40_u8] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(
+            Err(MovingAverageError::InvalidLength),
+            sut.try_set_active_window(5)
+        );
+        assert_eq!(4, sut.active_window());
+        assert_eq!(Ok(()), sut.try_set_active_window(2));
+        assert_eq!(Some(35_u8), sut.current_average());
+        assert_eq!(
+            Err(MovingAverageError::CountOverflow),
+            sut.try_transition_to(100_u8, 0x1_0000)
+        );
+        assert_eq!(35_u8, sut.average(30_u8));
+        assert_eq!(
+            Some(MovingAverageError::ZeroScale),
+            MovingAverage::<u8, u16, 4>::try_new_with_output_scale(0_u16, 1_u16).err()
+        );
+        assert_eq!(
+            Some(MovingAverageError::ScaleOverflow),
+            MovingAverage::<u8, u16, 256>::try_new_with_output_scale(2_u16, 1_u16).err()
+        );
+    }
+
+    #[cfg(feature = "defmt-checks")]
+    #[test]
+    fn given_defmt_checks_when_settings_are_bad_then_keep_safe_defaults() {
+        let mut sut = MovingAverage::<u8, u16, 4>::new_with_output_scale(0_u16, 1_u16);
+        assert_eq!(None, sut.output_scale);
+        for val in [10_u8, 20_u8, 30_u8, 40_u8] {
+            let _ = sut.average(val);
+        }
+        sut.set_active_window(5);
+        assert_eq!(4, sut.active_window());
+        sut.transition_to(100_u8, 0x1_0000);
+        assert_eq!(25_u8, sut.average(10_u8));
+    }
+
+    #[test]
+    fn given_wrapped_window_when_fold_then_visit_oldest_first() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        for val in [1_u16, 2_u16, 3_u16, 4_u16, 5_u16] {
+            let _ = sut.average(val);
+        }
+        let digits = sut.fold(0_u32, |total, &value| total * 10 + u32::from(value));
+        assert_eq!(345_u32, digits);
+    }
+
+    #[test]
+    fn given_new_moving_average_when_current_average_then_return_none() {
+        let sut = MovingAverage::<u32, u64, 2>::new();
+        assert_eq!(None, sut.current_average());
+    }
+
+    #[test]
+    fn given_polled_average_when_current_average_repeated_then_window_is_unchanged() {
+        let mut sut = MovingAverage::<u16, u32, 4, false>::new();
+        let _ = sut.average(10_u16);
+        let _ = sut.average(30_u16);
+        for _ in 0..8_usize {
+            assert_eq!(Some(20_u16), sut.current_average());
+        }
+        assert_eq!(2, sut.len());
+        assert_eq!(30_u16, sut.average(50_u16));
+    }
+
+    #[test]
+    fn given_samples_summing_to_zero_when_current_sum_then_distinguish_from_empty() {
+        let mut sut = MovingAverage::<i16, i32, 2>::new();
+        assert_eq!(None, sut.current_sum());
+        let _ = sut.average(-5_i16);
+        assert_eq!(Some(-10_i32), sut.current_sum());
+        let _ = sut.average(5_i16);
+        assert_eq!(Some(0_i32), sut.current_sum());
+    }
+
+    #[test]
+    fn given_sentinel_when_only_sentinels_averaged_then_current_average_is_none() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new_with_sentinel(0xFFFF_u16);
+        for _ in 0..4_usize {
+            assert_eq!(0xFFFF_u16, sut.average(0xFFFF_u16));
+        }
+        assert_eq!(None, sut.current_average());
+    }
+
+    #[test]
+    fn given_sentinel_when_interleaved_with_real_values_then_sentinels_are_skipped() {
+        let mut sut = MovingAverage::<u16, u32, 2>::new_with_sentinel(0xFFFF_u16);
+        assert_eq!(0xFFFF_u16, sut.average(0xFFFF_u16));
+        assert_eq!(100_u16, sut.average(100_u16));
+        assert_eq!(100_u16, sut.average(0xFFFF_u16));
+        assert_eq!(150_u16, sut.average(200_u16));
+        assert_eq!(150_u16, sut.average(0xFFFF_u16));
+        assert_eq!(250_u16, sut.average(300_u16));
+        assert_eq!(Some(250_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_averaged_values_when_buffer_recent_then_return_newest_value() {
+        let mut sut = MovingAverage::<u32, u64, 3>::new();
+        assert_eq!(None, sut.buffer().recent());
+        let _ = sut.average(3_u32);
+        let _ = sut.average(7_u32);
+        assert_eq!(Some(&7_u32), sut.buffer().recent());
+        assert_eq!(3, sut.buffer().len());
+    }
+
+    #[test]
+    fn given_rolled_over_window_when_pack_window_then_bytes_round_trip_to_window() {
+        let mut sut = MovingAverage::<i16, i32, 3>::new();
+        for val in [1_i16, -2_i16, 0x1234_i16, i16::MIN] {
+            let _ = sut.average(val);
+        }
+        let mut frame = [0_u8; 8];
+        assert_eq!(6, sut.pack_window(&mut frame));
+        assert_eq!(
+            [0xFE_u8, 0xFF_u8, 0x34_u8, 0x12_u8, 0x00_u8, 0x80_u8],
+            frame[..6]
+        );
+        let unpacked: [i16; 3] = [
+            i16::from_le_bytes([frame[0], frame[1]]),
+            i16::from_le_bytes([frame[2], frame[3]]),
+            i16::from_le_bytes([frame[4], frame[5]]),
+        ];
+        for (i, val) in sut.buffer().oldest_ordered().enumerate() {
+            assert_eq!(*val, unpacked[i], "Failed at {i}");
+        }
+    }
+
+    #[test]
+    fn given_short_frame_when_pack_window_then_write_nothing() {
+        let mut sut = MovingAverage::<u16, u32, 3>::new();
+        let _ = sut.average(7_u16);
+        let mut frame = [0_u8; 5];
+        assert_eq!(0, sut.pack_window(&mut frame));
+        assert_eq!([0_u8; 5], frame);
+    }
+
+    #[test]
+    fn given_new_moving_average_when_average_and_window_then_window_is_warm_up_fill() {
+        let mut sut = MovingAverage::<u32, u64, 3>::new();
+        let mut window = [0_u32; 3];
+        assert_eq!(9_u32, sut.average_and_window(9_u32, &mut window));
+        assert_eq!([9_u32, 9_u32, 9_u32], window);
+    }
+
+    #[test]
+    fn given_rolled_over_window_when_average_and_window_then_return_pre_insertion_window_and_post_insertion_average()
+     {
+        let mut sut = MovingAverage::<u32, u64, 3>::new();
+        let mut window = [0_u32; 3];
+        for val in [3_u32, 6_u32, 9_u32, 12_u32] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(12_u32, sut.average_and_window(15_u32, &mut window));
+        assert_eq!([6_u32, 9_u32, 12_u32], window);
+        assert_eq!(15_u32, sut.average_and_window(18_u32, &mut window));
+        assert_eq!([9_u32, 12_u32, 15_u32], window);
+    }
+
+    #[test]
+    fn given_known_sequence_when_mean_consecutive_diff_then_return_mean_absolute_difference() {
+        let mut sut = MovingAverage::<i16, i32, 5>::new();
+        for val in [10_i16, 13_i16, 9_i16, 9_i16, -3_i16, 5_i16] {
+            let _ = sut.average(val);
+        }
+        // window is [13, 9, 9, -3, 5], differences are 4, 0, 12, 8
+        assert_eq!(Some(6_i32), sut.mean_consecutive_diff());
+    }
+
+    #[test]
+    fn given_new_moving_average_when_mean_consecutive_diff_then_return_none() {
+        let sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.mean_consecutive_diff());
+    }
+
+    #[test]
+    fn given_depth_of_one_when_mean_consecutive_diff_then_return_none() {
+        let mut sut = MovingAverage::<u16, u32, 1>::new();
+        let _ = sut.average(7_u16);
+        assert_eq!(None, sut.mean_consecutive_diff());
+    }
+
+    #[test]
+    fn given_window_with_clear_mode_when_mode_count_then_return_mode_and_count() {
+        let mut sut = MovingAverage::<i16, i32, 6>::new();
+        for val in [4_i16, -2_i16, 9_i16, -2_i16, 4_i16, -2_i16, 9_i16] {
+            let _ = sut.average(val);
+        }
+        // window is [-2, 9, -2, 4, -2, 9]
+        assert_eq!((Some(-2_i16), 3), sut.mode_count());
+    }
+
+    #[test]
+    fn given_flat_window_when_mode_count_then_return_full_count() {
+        let mut sut = MovingAverage::<u16, u32, 5>::new();
+        let _ = sut.average(42_u16);
+        assert_eq!((Some(42_u16), 5), sut.mode_count());
+    }
+
+    #[test]
+    fn given_tied_window_when_mode_count_then_return_smallest_mode() {
+        let mut sut = MovingAverage::<u16, u32, 4>::new();
+        for val in [8_u16, 8_u16, 3_u16, 3_u16] {
+            let _ = sut.average(val);
+        }
+        assert_eq!((Some(3_u16), 2), sut.mode_count());
+    }
+
+    #[test]
+    fn given_new_moving_average_when_mode_count_then_return_none() {
+        let sut = MovingAverage::<u16, u32, 4>::new();
+        assert_eq!((None, 0), sut.mode_count());
+    }
+
+    #[test]
+    fn given_new_moving_average_when_headroom_then_return_tcalc_max() {
+        let sut = MovingAverage::<u8, u16, 4>::new();
+        assert_eq!(u16::MAX, sut.headroom());
+    }
+
+    #[test]
+    fn given_rising_data_when_headroom_then_headroom_shrinks() {
+        let mut sut = MovingAverage::<u8, u16, 256>::new();
+        let _ = sut.average(100_u8);
+        assert_eq!(u16::MAX - 25_600_u16, sut.headroom());
+        let _ = sut.average(u8::MAX);
+        assert_eq!(u16::MAX - 25_755_u16, sut.headroom());
+        for _ in 0..256_usize {
+            let _ = sut.average(u8::MAX);
         }
+        assert_eq!(255_u16, sut.headroom());
     }
 
     #[test]
-    #[should_panic(expected = "N * T.max() must fit in TCALC")]
-    fn confirm_n_times_t_max_fits_in_tcalc() {
-        let _sut = MovingAverage::<u8, u16, 512>::new();
+    fn given_negative_data_when_headroom_then_return_distance_to_min() {
+        let mut sut = MovingAverage::<i8, i16, 256>::new();
+        let _ = sut.average(-100_i8);
+        assert_eq!(7_168_i16, sut.headroom());
+        let _ = sut.average(i8::MIN);
+        assert_eq!(7_140_i16, sut.headroom());
+        for _ in 0..256_usize {
+            let _ = sut.average(i8::MIN);
+        }
+        assert_eq!(0_i16, sut.headroom());
+    }
+
+    #[test]
+    fn given_valid_capacity_when_check_capacity_then_return_ok() {
+        assert_eq!(Ok(()), MovingAverage::<u8, u16, 256>::check_capacity());
+    }
+
+    #[test]
+    fn given_invalid_capacity_when_check_capacity_then_return_capacity_overflow() {
+        assert_eq!(
+            Err(MovingAverageError::CapacityOverflow),
+            MovingAverage::<u8, u16, 512>::check_capacity()
+        );
     }
 
     // fails at compile time, due to missing conversions
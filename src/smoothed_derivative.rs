@@ -0,0 +1,210 @@
+use heapless::HistoryBuffer;
+
+use crate::{Accumulator, MovingAverage, Sample};
+
+/// # Intent
+/// Wraps a `MovingAverage` to report the per-sample rate of change of its
+/// average, smoothed over a number of steps. The last M averages are kept
+/// in a history of their own, so filters that don't need the derivative
+/// don't pay for the storage.
+///
+/// The smoothing is a moving average of the step deltas. Because the
+/// deltas telescope, it is calculated from the two averages
+/// `deriv_window` steps apart, so `deriv_window` can be up to `M - 1`.
+/// Every sample goes through this type, so the history holds every
+/// average and the deltas can't be skewed by samples fed around it.
+///
+/// PREFILL and SATURATING are passed on to the wrapped `MovingAverage`.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::SmoothedDerivative;
+///
+/// let mut sut = SmoothedDerivative::<u32, u64, 4, 3>::new();
+/// for sample in [0, 4, 8, 12, 16, 20] {
+///     let _ = sut.average(sample);
+/// }
+///
+/// assert_eq!(Some(4), sut.derivative(2));
+/// ```
+pub struct SmoothedDerivative<
+    T,
+    TCALC,
+    const N: usize,
+    const M: usize,
+    const PREFILL: bool = true,
+    const SATURATING: bool = false,
+> where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    filter: MovingAverage<T, TCALC, N, PREFILL, SATURATING>,
+    averages: HistoryBuffer<T, M>,
+}
+
+impl<T, TCALC, const N: usize, const M: usize, const PREFILL: bool, const SATURATING: bool> Default
+    for SmoothedDerivative<T, TCALC, N, M, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn default() -> Self {
+        Self {
+            filter: MovingAverage::new(),
+            averages: HistoryBuffer::new(),
+        }
+    }
+}
+
+impl<T, TCALC, const N: usize, const M: usize, const PREFILL: bool, const SATURATING: bool>
+    SmoothedDerivative<T, TCALC, N, M, PREFILL, SATURATING>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    /// # Panics
+    /// Panics as `MovingAverage::new`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Intent
+    /// Averages `input`, recording the average for the derivative, and
+    /// returns it.
+    ///
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T, as `MovingAverage::average`.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        let average = self.filter.average(input);
+        self.averages.write(average);
+        average
+    }
+
+    /// # Intent
+    /// Feeds `input` through `average` and returns the per-sample rate of
+    /// change of the average, smoothed over the last `deriv_window` steps.
+    #[must_use]
+    pub fn smoothed_derivative(&mut self, input: T, deriv_window: usize) -> Option<T> {
+        let average = self.filter.average(input);
+        self.averages.write(average);
+        self.derivative(deriv_window)
+    }
+
+    /// # Intent
+    /// Returns the per-sample rate of change of the average over the last
+    /// `deriv_window` steps, without inserting a sample.
+    ///
+    /// Returns `None` until `deriv_window + 1` averages have been produced,
+    /// if `deriv_window` is not in `1..M`, or if the rate cannot be
+    /// represented in T (a falling signal with unsigned T).
+    #[must_use]
+    pub fn derivative(&self, deriv_window: usize) -> Option<T> {
+        if deriv_window == 0 || self.averages.len() <= deriv_window {
+            return None;
+        }
+        let newest = *self.averages.recent()?;
+        let past = *self
+            .averages
+            .oldest_ordered()
+            .nth(self.averages.len() - 1 - deriv_window)?;
+        let newest_calc = TCALC::from(newest);
+        let past_calc = TCALC::from(past);
+        if newest_calc < past_calc && !TCALC::SIGNED {
+            return None;
+        }
+        let steps = TCALC::try_from(deriv_window).ok()?;
+        T::try_from((newest_calc - past_calc) / steps).ok()
+    }
+
+    /// # Intent
+    /// Returns the wrapped filter, for its statistics.
+    #[must_use]
+    pub fn filter(&self) -> &MovingAverage<T, TCALC, N, PREFILL, SATURATING> {
+        &self.filter
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::SmoothedDerivative;
+
+    #[test]
+    fn given_constant_velocity_ramp_when_smoothed_derivative_then_return_ramp_rate() {
+        let mut sut = SmoothedDerivative::<u32, u64, 4, 4>::new();
+        let mut result = None;
+        for i in 0..8 {
+            result = sut.smoothed_derivative(4 * i, 2);
+            if i < 2 {
+                assert_eq!(None, result, "Failed at {i}, should not be warmed up");
+            }
+        }
+        assert_eq!(Some(4), result);
+        assert_eq!(Some(4), sut.smoothed_derivative(32, 2));
+    }
+
+    #[test]
+    fn given_falling_signed_ramp_when_smoothed_derivative_then_return_negative_rate() {
+        let mut sut = SmoothedDerivative::<i32, i64, 4, 4>::new();
+        let mut result = None;
+        for i in 0_i32..8_i32 {
+            result = sut.smoothed_derivative(-4_i32 * i, 2);
+        }
+        assert_eq!(Some(-4_i32), result);
+    }
+
+    #[test]
+    fn given_falling_unsigned_ramp_when_smoothed_derivative_then_return_none() {
+        let mut sut = SmoothedDerivative::<u32, u64, 4, 4>::new();
+        let mut result = Some(0);
+        for i in 0..8 {
+            result = sut.smoothed_derivative(100 - 4 * i, 2);
+        }
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn given_window_outside_history_when_smoothed_derivative_then_return_none() {
+        let mut sut = SmoothedDerivative::<u32, u64, 4, 4>::new();
+        for i in 0..8 {
+            assert_eq!(None, sut.smoothed_derivative(4 * i, 4));
+            assert_eq!(None, sut.smoothed_derivative(4 * i, 0));
+        }
+    }
+
+    #[test]
+    fn given_history_longer_than_depth_when_derivative_then_reach_past_n() {
+        let mut sut = SmoothedDerivative::<u32, u64, 2, 8>::new();
+        for i in 0..10 {
+            let _ = sut.average(3 * i);
+        }
+        assert_eq!(Some(3), sut.derivative(6));
+        assert_eq!(Some(27), sut.filter().newest());
+    }
+
+    #[test]
+    fn given_mixed_average_and_smoothed_derivative_calls_then_every_average_is_recorded() {
+        let mut sut = SmoothedDerivative::<u32, u64, 1, 4>::new();
+        let _ = sut.average(10);
+        let _ = sut.average(20);
+        assert_eq!(Some(10), sut.smoothed_derivative(30, 2));
+    }
+
+    #[test]
+    fn given_no_prefill_when_derivative_during_warm_up_then_follow_partial_averages() {
+        let mut sut = SmoothedDerivative::<u32, u64, 4, 4, false>::new();
+        let mut prefilled = SmoothedDerivative::<u32, u64, 4, 4>::new();
+        for sample in [0, 4, 8] {
+            let _ = sut.average(sample);
+            let _ = prefilled.average(sample);
+        }
+        // Averages 0, 2, 4 over the samples seen, against 0, 1, 3 prefilled.
+        assert_eq!(Some(2), sut.derivative(2));
+        assert_eq!(Some(1), prefilled.derivative(2));
+        assert_eq!(3, sut.filter().len());
+    }
+}
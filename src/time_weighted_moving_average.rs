@@ -0,0 +1,241 @@
+use core::mem::size_of;
+use heapless::HistoryBuffer;
+
+use crate::{
+    Accumulator, MovingAverageError, Sample,
+    error::{convert, finish_check, unwrap_check},
+};
+
+/// # Intent
+/// Creates a moving average over the last N samples of an irregularly
+/// sampled signal, where each sample counts in proportion to how long it
+/// was the current reading. The average is `sum(x_i * dt_i) / sum(dt_i)`,
+/// truncated towards zero.
+///
+/// The duration of each sample is stored alongside it, and both sums are
+/// kept running, so each update costs O(1). There is no warm-up fill:
+/// until N samples have arrived, the average is over those seen.
+///
+/// The accumulated time is held in TCALC. The time of the window times
+/// `T::MAX`, and times `T::MIN` for a signed T, must fit in TCALC, e.g. a
+/// `u16` in `u64` allows about 2^48 ticks per window. This depends on the
+/// durations fed, so unlike the depth it can't be checked at construction.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::TimeWeightedMovingAverage;
+///
+/// let mut sut = TimeWeightedMovingAverage::<u16, u32, 4>::new();
+/// let _ = sut.average(10, 1);
+///
+/// assert_eq!(25, sut.average(30, 3));
+/// ```
+pub struct TimeWeightedMovingAverage<T, TCALC, const N: usize>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    weighted_sum: TCALC,
+    elapsed: TCALC,
+    buffer: HistoryBuffer<(T, u32), N>,
+}
+
+impl<T, TCALC, const N: usize> Default for TimeWeightedMovingAverage<T, TCALC, N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T> + TryFrom<u32>,
+{
+    fn default() -> Self {
+        const {
+            assert!(
+                size_of::<TCALC>() > size_of::<T>(),
+                "TCALC must be larger than T"
+            );
+            assert!(N > 0, "N must be non-zero");
+        }
+        Self {
+            weighted_sum: TCALC::ZERO,
+            elapsed: TCALC::ZERO,
+            buffer: HistoryBuffer::new(),
+        }
+    }
+}
+
+impl<T, TCALC, const N: usize> TimeWeightedMovingAverage<T, TCALC, N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T> + TryFrom<u32>,
+{
+    /// # Panics
+    /// Panics if TCALC not larger than T, compile-time assert.
+    /// Panics if N is zero, compile-time assert.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Intent
+    /// Averages `input`, which was the current reading for the last `dt`
+    /// ticks, and returns the time-weighted average of the window. The
+    /// oldest sample, with its duration, leaves once N samples are held.
+    ///
+    /// A `dt` of zero gives `input` no weight. While the window holds no
+    /// time at all, `input` is returned.
+    ///
+    /// # Panics
+    /// Panics if `dt` won't fit in TCALC. Only a TCALC narrower than u32,
+    /// or an i32, allows this. The conversion doesn't go through usize, so
+    /// doesn't depend on the target's pointer width.
+    /// With the `defmt-checks` feature, logs the error and skips the sample
+    /// instead, returning the current average.
+    /// The accumulated time can overflow TCALC, see the type's docs.
+    #[must_use]
+    pub fn average(&mut self, input: T, dt: u32) -> T {
+        let result = self.insert(input, dt);
+        finish_check(result, || self.current_average().unwrap_or(input))
+    }
+
+    /// # Intent
+    /// Averages `input` over `dt` ticks as `average` does, for callers that
+    /// handle a `dt` too long for TCALC themselves.
+    ///
+    /// # Errors
+    /// Returns `CountOverflow` if `dt` won't fit in TCALC, leaving the
+    /// window unchanged.
+    #[cfg(feature = "result-checks")]
+    pub fn try_average(&mut self, input: T, dt: u32) -> Result<T, MovingAverageError> {
+        self.insert(input, dt)
+    }
+
+    /// # Intent
+    /// Returns the time-weighted average without inserting a sample, or
+    /// `None` if the window holds no time.
+    #[must_use]
+    pub fn current_average(&self) -> Option<T> {
+        (self.elapsed != TCALC::ZERO).then(|| Self::narrow(self.weighted_sum / self.elapsed))
+    }
+
+    /// # Intent
+    /// Returns the total duration of the samples in the window, in ticks,
+    /// e.g. to check it against the overflow limit.
+    #[must_use]
+    pub fn elapsed(&self) -> TCALC {
+        self.elapsed
+    }
+
+    fn insert(&mut self, input: T, dt: u32) -> Result<T, MovingAverageError> {
+        let ticks = Self::ticks(dt)?;
+        let (remove_sum, remove_elapsed) = match self.buffer.oldest_ordered().next() {
+            Some(&(oldest, oldest_dt)) if self.buffer.len() == N => {
+                let oldest_ticks = Self::ticks(oldest_dt)?;
+                (TCALC::from(oldest) * oldest_ticks, oldest_ticks)
+            }
+            Some(_) | None => (TCALC::ZERO, TCALC::ZERO),
+        };
+        self.buffer.write((input, dt));
+        // Removing first keeps the intermediates within the sums of the window.
+        self.weighted_sum = self.weighted_sum - remove_sum + TCALC::from(input) * ticks;
+        self.elapsed = self.elapsed - remove_elapsed + ticks;
+        Ok(self.current_average().unwrap_or(input))
+    }
+
+    fn ticks(dt: u32) -> Result<TCALC, MovingAverageError> {
+        convert(dt, MovingAverageError::CountOverflow)
+    }
+
+    fn narrow(average_as_tcalc: TCALC) -> T {
+        unwrap_check(convert(
+            average_as_tcalc,
+            MovingAverageError::OutputOverflow,
+        ))
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::TimeWeightedMovingAverage;
+    #[cfg(feature = "result-checks")]
+    use crate::MovingAverageError;
+
+    #[test]
+    fn given_uneven_dt_when_average_then_weight_by_duration() {
+        let mut sut = TimeWeightedMovingAverage::<u16, u32, 3>::new();
+        assert_eq!(None, sut.current_average());
+        assert_eq!(100_u16, sut.average(100_u16, 1));
+        // (100 * 1 + 200 * 3) / 4
+        assert_eq!(175_u16, sut.average(200_u16, 3));
+        // (100 * 1 + 200 * 3 + 40 * 6) / 10
+        assert_eq!(94_u16, sut.average(40_u16, 6));
+        assert_eq!(10_u32, sut.elapsed());
+    }
+
+    #[test]
+    fn given_full_window_when_average_then_evict_oldest_sample_and_duration() {
+        let mut sut = TimeWeightedMovingAverage::<u16, u32, 2>::new();
+        let _ = sut.average(1000_u16, 50);
+        let _ = sut.average(10_u16, 2);
+        // (10 * 2 + 30 * 8) / 10, the 1000 and its 50 ticks evicted.
+        assert_eq!(26_u16, sut.average(30_u16, 8));
+        assert_eq!(10_u32, sut.elapsed());
+        assert_eq!(Some(26_u16), sut.current_average());
+    }
+
+    #[test]
+    fn given_signed_samples_when_average_then_truncate_towards_zero() {
+        let mut sut = TimeWeightedMovingAverage::<i16, i32, 4>::new();
+        let _ = sut.average(-10_i16, 2);
+        // (-10 * 2 + 5 * 1) / 3
+        assert_eq!(-5_i16, sut.average(5_i16, 1));
+    }
+
+    #[test]
+    fn given_dt_beyond_u16_when_average_with_u32_tcalc_then_weight_it() {
+        let mut sut = TimeWeightedMovingAverage::<u8, u32, 2>::new();
+        let _ = sut.average(10_u8, 0x1_0000);
+        // (10 * 0x10000 + 50 * 0x30000) / 0x40000
+        assert_eq!(40_u8, sut.average(50_u8, 0x3_0000));
+        assert_eq!(0x4_0000_u32, sut.elapsed());
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "count must fit in TCALC")]
+    fn confirm_dt_beyond_tcalc_panics() {
+        let mut sut = TimeWeightedMovingAverage::<u8, u16, 2>::new();
+        let _ = sut.average(10_u8, 0x1_0000);
+    }
+
+    #[test]
+    fn given_zero_dt_when_average_then_sample_has_no_weight() {
+        let mut sut = TimeWeightedMovingAverage::<u8, u16, 3>::new();
+        assert_eq!(7_u8, sut.average(7_u8, 0));
+        assert_eq!(None, sut.current_average());
+        assert_eq!(20_u8, sut.average(20_u8, 5));
+        assert_eq!(20_u8, sut.average(90_u8, 0));
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_dt_beyond_tcalc_when_try_average_then_return_error_and_keep_window() {
+        let mut sut = TimeWeightedMovingAverage::<u8, u16, 2>::new();
+        assert_eq!(Ok(10_u8), sut.try_average(10_u8, 4));
+        assert_eq!(
+            Err(MovingAverageError::CountOverflow),
+            sut.try_average(200_u8, 0x1_0000)
+        );
+        assert_eq!(4_u16, sut.elapsed());
+        assert_eq!(Ok(20_u8), sut.try_average(30_u8, 4));
+    }
+
+    #[cfg(feature = "defmt-checks")]
+    #[test]
+    fn given_defmt_checks_when_dt_is_beyond_tcalc_then_skip_the_sample() {
+        let mut sut = TimeWeightedMovingAverage::<u8, u16, 2>::new();
+        let _ = sut.average(10_u8, 4);
+        assert_eq!(10_u8, sut.average(200_u8, 0x1_0000));
+        assert_eq!(4_u16, sut.elapsed());
+    }
+}
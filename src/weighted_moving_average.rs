@@ -0,0 +1,300 @@
+use core::mem::size_of;
+use heapless::HistoryBuffer;
+
+use crate::{
+    Accumulator, Coefficients, MovingAverageError, Sample,
+    error::{check, convert, finish_check, unwrap_check},
+};
+
+/// # Intent
+/// Creates a weighted moving average, where each window position has its
+/// own weight, so recent samples can count more than old ones. The average
+/// is `sum(w_i * x_i) / sum(w_i)`, truncated towards zero.
+///
+/// By default the weights are a linear ramp, 1 for the oldest sample up
+/// to N for the newest. Others can be supplied with `with_weights`.
+///
+/// Unlike `MovingAverage` there is no running sum, as every weight moves
+/// with each sample, so each update costs O(N).
+/// `sum(w_i) * T::MAX` must fit in TCALC.
+///
+/// As with `MovingAverage`, the first insertion stuffs the entire buffer
+/// with the first value.
+///
+/// # Example
+///
+/// ```rust
+/// use no_std_moving_average::WeightedMovingAverage;
+///
+/// let mut sut = WeightedMovingAverage::<u16, u32, 4>::new();
+/// let _ = sut.average(10);
+///
+/// assert_eq!(14, sut.average(20));
+/// ```
+pub struct WeightedMovingAverage<T, TCALC, const N: usize>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    weights: [TCALC; N],
+    weight_sum: TCALC,
+    buffer: HistoryBuffer<T, N>,
+}
+
+/// # Panics
+/// Panics as `with_weights`, for the linear ramp.
+/// Panics if N won't fit in TCALC.
+impl<T, TCALC, const N: usize> Default for WeightedMovingAverage<T, TCALC, N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn default() -> Self {
+        let mut weights = [TCALC::ZERO; N];
+        for (i, weight) in weights.iter_mut().enumerate() {
+            *weight = unwrap_check(convert(i + 1, MovingAverageError::CountOverflow));
+        }
+        unwrap_check(Self::with_checked_weights(weights))
+    }
+}
+
+impl<T, TCALC, const N: usize> WeightedMovingAverage<T, TCALC, N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Intent
+    /// Creates a filter with the given weight for each window position,
+    /// oldest first.
+    ///
+    /// # Panics
+    /// Panics if TCALC not larger than T, compile-time assert.
+    /// Panics if N is zero, compile-time assert.
+    /// Panics if a weight is negative, or the weights sum to zero.
+    /// Panics if `sum(weights) * T::MAX` won't fit in TCALC.
+    /// : These panics happen at allocation time, so should be found predictably.
+    /// With the `defmt-checks` feature, logs the error and weights only the
+    /// newest sample instead, passing the input through.
+    #[must_use]
+    pub fn with_weights(weights: [TCALC; N]) -> Self {
+        finish_check(Self::with_checked_weights(weights), Self::newest_only)
+    }
+
+    /// # Intent
+    /// Creates a filter with the given weights as `with_weights` does, for
+    /// callers that handle bad weights themselves.
+    ///
+    /// # Errors
+    /// Returns `InvalidWeights` if a weight is negative or the weights sum
+    /// to zero, or `WeightedOverflow` if `sum(weights) * T::MAX` won't fit
+    /// in TCALC.
+    #[cfg(feature = "result-checks")]
+    pub fn try_with_weights(weights: [TCALC; N]) -> Result<Self, MovingAverageError> {
+        Self::with_checked_weights(weights)
+    }
+
+    /// # Panics
+    /// Panics if unable to convert from TCALC to T.
+    /// This panic should never occur due to the allocation time asserts.
+    #[must_use]
+    pub fn average(&mut self, input: T) -> T {
+        if self.buffer.is_empty() {
+            for _ in 0..N {
+                self.buffer.write(input);
+            }
+        }
+        self.buffer.write(input);
+        self.weighted_mean()
+    }
+
+    /// # Intent
+    /// Returns the current average without inserting a sample, or `None`
+    /// if no samples have been recorded.
+    #[must_use]
+    pub fn current_average(&self) -> Option<T> {
+        (!self.buffer.is_empty()).then(|| self.weighted_mean())
+    }
+
+    fn with_checked_weights(weights: [TCALC; N]) -> Result<Self, MovingAverageError> {
+        const {
+            assert!(
+                size_of::<TCALC>() > size_of::<T>(),
+                "TCALC must be larger than T"
+            );
+            assert!(N > 0, "N must be non-zero");
+        }
+        let weight_sum = Self::check_weights(&weights)?;
+        Ok(Self {
+            weights,
+            weight_sum,
+            buffer: HistoryBuffer::new(),
+        })
+    }
+
+    /// The filter a failed check falls back to with the `defmt-checks`
+    /// feature. A single weight of one always fits, as TCALC holds T.
+    fn newest_only() -> Self {
+        let mut weights = [TCALC::ZERO; N];
+        weights[N - 1] = TCALC::ONE;
+        Self {
+            weights,
+            weight_sum: TCALC::ONE,
+            buffer: HistoryBuffer::new(),
+        }
+    }
+
+    /// Returns the sum of the weights, checking that none is negative and
+    /// that the weighted sum of any window fits in TCALC.
+    fn check_weights(weights: &[TCALC; N]) -> Result<TCALC, MovingAverageError> {
+        let mut weight_sum = TCALC::ZERO;
+        for &weight in weights {
+            let non_negative = weight >= TCALC::ZERO;
+            check!(non_negative, MovingAverageError::InvalidWeights);
+            let fits = weight <= TCALC::MAX - weight_sum;
+            check!(fits, MovingAverageError::WeightedOverflow);
+            weight_sum = weight_sum + weight;
+        }
+        let non_zero = weight_sum > TCALC::ZERO;
+        check!(non_zero, MovingAverageError::InvalidWeights);
+        let fits = weight_sum <= TCALC::MAX / TCALC::from(T::MAX)
+            && TCALC::from(T::MIN) >= TCALC::MIN / weight_sum;
+        check!(fits, MovingAverageError::WeightedOverflow);
+        Ok(weight_sum)
+    }
+
+    fn weighted_mean(&self) -> T {
+        let weighted_sum = self
+            .buffer
+            .oldest_ordered()
+            .zip(self.weights)
+            .fold(TCALC::ZERO, |sum, (&value, weight)| {
+                sum + TCALC::from(value) * weight
+            });
+        unwrap_check(convert(
+            weighted_sum / self.weight_sum,
+            MovingAverageError::OutputOverflow,
+        ))
+    }
+}
+
+/// # Intent
+/// The weights as supplied, oldest first, summing to the divisor.
+impl<T, TCALC, const N: usize> Coefficients<TCALC, N> for WeightedMovingAverage<T, TCALC, N>
+where
+    T: Sample<TCALC>,
+    TCALC: Accumulator<T>,
+{
+    fn coefficients(&self) -> [TCALC; N] {
+        self.weights
+    }
+}
+
+#[expect(clippy::let_underscore_must_use, reason = "Desirable in tests")]
+#[expect(clippy::let_underscore_untyped, reason = "Desirable in tests")]
+#[cfg(test)]
+mod tests {
+    use super::WeightedMovingAverage;
+    use crate::{Coefficients as _, MovingAverageError};
+
+    #[test]
+    fn given_linear_ramp_when_average_then_match_hand_computed_weighted_means() {
+        let mut sut = WeightedMovingAverage::<u16, u32, 4>::new();
+        assert_eq!(None, sut.current_average());
+        assert_eq!(10_u16, sut.average(10_u16));
+        // Weights 1, 2, 3, 4 over the window oldest first, divided by 10.
+        for (val, expected) in [(20_u16, 14_u16), (30, 21), (40, 30), (50, 40)] {
+            assert_eq!(expected, sut.average(val));
+        }
+        assert_eq!(Some(40_u16), sut.current_average());
+        assert_eq!([1_u32, 2_u32, 3_u32, 4_u32], sut.coefficients());
+    }
+
+    #[test]
+    fn given_signed_samples_when_average_then_truncate_towards_zero() {
+        let mut sut = WeightedMovingAverage::<i16, i32, 3>::new();
+        let _ = sut.average(0_i16);
+        // (0 * 1 + 0 * 2 - 100 * 3) / 6
+        assert_eq!(-50_i16, sut.average(-100_i16));
+        // (0 * 1 - 100 * 2 + 7 * 3) / 6
+        assert_eq!(-29_i16, sut.average(7_i16));
+    }
+
+    #[test]
+    fn given_supplied_weights_when_average_then_use_them() {
+        let mut sut = WeightedMovingAverage::<u8, u16, 4>::with_weights([0, 0, 1, 1]);
+        for val in [10_u8, 20_u8, 30_u8, 41_u8] {
+            let _ = sut.average(val);
+        }
+        assert_eq!(Some(35_u8), sut.current_average());
+    }
+
+    #[test]
+    fn given_weights_when_check_weights_then_report_each_failure() {
+        assert_eq!(
+            Ok(253_u16),
+            WeightedMovingAverage::<u8, u16, 22>::check_weights(
+                &WeightedMovingAverage::<u8, u16, 22>::new().weights
+            )
+        );
+        assert_eq!(
+            Err(MovingAverageError::InvalidWeights),
+            WeightedMovingAverage::<i8, i16, 2>::check_weights(&[2, -1])
+        );
+        assert_eq!(
+            Err(MovingAverageError::InvalidWeights),
+            WeightedMovingAverage::<u8, u16, 2>::check_weights(&[0, 0])
+        );
+        assert_eq!(
+            Err(MovingAverageError::WeightedOverflow),
+            WeightedMovingAverage::<u8, u16, 2>::check_weights(&[0xFFFF, 1])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "sum(weights) * T.max() must fit in TCALC")]
+    fn confirm_linear_ramp_sum_times_t_max_fits_in_tcalc() {
+        let _sut = WeightedMovingAverage::<u8, u16, 23>::new();
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "sum(weights) * T.max() must fit in TCALC")]
+    fn confirm_weight_sum_times_t_min_fits_in_signed_tcalc() {
+        let _sut = WeightedMovingAverage::<i8, i16, 1>::with_weights([0x101]);
+    }
+
+    #[cfg(not(feature = "defmt-checks"))]
+    #[test]
+    #[should_panic(expected = "weights must be non-negative, with a non-zero sum")]
+    fn confirm_weights_are_not_all_zero() {
+        let _sut = WeightedMovingAverage::<u16, u32, 2>::with_weights([0, 0]);
+    }
+
+    #[cfg(feature = "result-checks")]
+    #[test]
+    fn given_bad_weights_when_try_with_weights_then_return_error() {
+        assert_eq!(
+            Some(MovingAverageError::InvalidWeights),
+            WeightedMovingAverage::<u16, u32, 2>::try_with_weights([0, 0]).err()
+        );
+        assert_eq!(
+            Some(MovingAverageError::WeightedOverflow),
+            WeightedMovingAverage::<i8, i16, 1>::try_with_weights([0x101]).err()
+        );
+        assert!(WeightedMovingAverage::<u8, u16, 4>::try_with_weights([0, 0, 1, 1]).is_ok());
+    }
+
+    #[cfg(feature = "defmt-checks")]
+    #[test]
+    fn given_defmt_checks_when_weights_are_bad_then_weight_only_the_newest_sample() {
+        let mut sut = WeightedMovingAverage::<u16, u32, 3>::with_weights([0, 0, 0]);
+        assert_eq!([0_u32, 0_u32, 1_u32], sut.coefficients());
+        let _ = sut.average(10_u16);
+        assert_eq!(70_u16, sut.average(70_u16));
+    }
+}